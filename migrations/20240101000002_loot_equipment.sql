@@ -0,0 +1,4 @@
+-- Equipped items occupy one slot per item class
+ALTER TABLE loot ADD COLUMN equipped BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE UNIQUE INDEX idx_loot_equipped_slot ON loot(chat_id, owner, class_name) WHERE equipped;
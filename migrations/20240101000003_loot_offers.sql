@@ -0,0 +1,16 @@
+-- Pending gifts and trades of loot between players of the same chat
+CREATE TABLE loot_offers (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    from_user BIGINT NOT NULL,
+    to_user BIGINT NOT NULL,
+    offered_uuid UUID NOT NULL,
+    requested_uuid UUID,
+    from_accepted BOOLEAN NOT NULL DEFAULT FALSE,
+    to_accepted BOOLEAN NOT NULL DEFAULT FALSE,
+    status TEXT NOT NULL DEFAULT 'pending',
+    expires_at DOUBLE PRECISION NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_loot_offers_chat_status ON loot_offers(chat_id, status);
@@ -0,0 +1,17 @@
+-- Weight-wagering duels between pigs of the same chat
+CREATE TABLE duels (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    challenger_id BIGINT NOT NULL,
+    target_id BIGINT NOT NULL,
+    wager INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    winner_id BIGINT,
+    transferred INTEGER NOT NULL DEFAULT 0,
+    expires_at DOUBLE PRECISION NOT NULL,
+    resolved_at DOUBLE PRECISION,
+    created_at TIMESTAMP WITH TIME ZONE DEFAULT NOW()
+);
+
+CREATE INDEX idx_duels_chat_challenger ON duels(chat_id, challenger_id);
+CREATE INDEX idx_duels_chat_target ON duels(chat_id, target_id);
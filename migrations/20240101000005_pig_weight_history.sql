@@ -0,0 +1,11 @@
+-- One row per feed, for /history charts
+CREATE TABLE pig_weight_history (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    weight INTEGER NOT NULL,
+    growth INTEGER NOT NULL,
+    recorded_at DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX idx_weight_history_pig_time ON pig_weight_history(chat_id, user_id, recorded_at);
@@ -0,0 +1,9 @@
+-- Per-chat preferences
+CREATE TABLE chat_settings (
+    chat_id BIGINT PRIMARY KEY,
+    global_opt_out BOOLEAN NOT NULL DEFAULT FALSE
+);
+
+-- Cross-chat lookups for /globaltop and /profile
+CREATE INDEX idx_pigs_user ON pigs(user_id);
+CREATE INDEX idx_pigs_weight ON pigs(weight DESC);
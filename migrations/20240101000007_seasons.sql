@@ -0,0 +1,24 @@
+-- Per-chat seasons; the row with ended_at IS NULL is the running one
+CREATE TABLE seasons (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    number INTEGER NOT NULL,
+    started_at DOUBLE PRECISION NOT NULL,
+    ended_at DOUBLE PRECISION,
+    UNIQUE(chat_id, number)
+);
+
+CREATE UNIQUE INDEX idx_seasons_active ON seasons(chat_id) WHERE ended_at IS NULL;
+
+-- Final standings snapshotted when a season ends
+CREATE TABLE season_standings (
+    season_id INTEGER NOT NULL REFERENCES seasons(id),
+    position INTEGER NOT NULL,
+    user_id BIGINT NOT NULL,
+    pig_name TEXT NOT NULL,
+    owner_name TEXT NOT NULL,
+    weight INTEGER NOT NULL,
+    PRIMARY KEY (season_id, position)
+);
+
+CREATE INDEX idx_season_standings_user ON season_standings(user_id, position);
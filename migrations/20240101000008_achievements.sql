@@ -0,0 +1,15 @@
+-- Unlocked achievements, one row per pig and achievement key
+CREATE TABLE pig_achievements (
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    key TEXT NOT NULL,
+    unlocked_at DOUBLE PRECISION NOT NULL,
+    PRIMARY KEY (chat_id, user_id, key)
+);
+
+-- Current heaviest pig of each chat and since when it holds first place
+CREATE TABLE chat_leaders (
+    chat_id BIGINT PRIMARY KEY,
+    user_id BIGINT NOT NULL,
+    since DOUBLE PRECISION NOT NULL
+);
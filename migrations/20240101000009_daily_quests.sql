@@ -0,0 +1,16 @@
+-- Three random quests per player and day; `day` counts days since the epoch in the chat's time zone
+CREATE TABLE daily_quests (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    day BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    target INTEGER NOT NULL,
+    progress INTEGER NOT NULL DEFAULT 0,
+    reward INTEGER NOT NULL,
+    completed BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE(chat_id, user_id, day, kind)
+);
+
+-- Hours from UTC at which the chat's quest day starts
+ALTER TABLE chat_settings ADD COLUMN utc_offset INTEGER NOT NULL DEFAULT 0;
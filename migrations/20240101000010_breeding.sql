@@ -0,0 +1,24 @@
+-- Inherited traits of a pig; pigs without a row have neutral genes
+CREATE TABLE pig_genetics (
+    pig_id INTEGER PRIMARY KEY REFERENCES pigs(id) ON DELETE CASCADE,
+    growth_bias DOUBLE PRECISION NOT NULL DEFAULT 0,
+    loss_resistance DOUBLE PRECISION NOT NULL DEFAULT 0,
+    duel_strength DOUBLE PRECISION NOT NULL DEFAULT 0,
+    last_bred DOUBLE PRECISION NOT NULL DEFAULT 0
+);
+
+-- Piglets born from /breed, waiting in their owner's stable
+CREATE TABLE piglets (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    owner BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    growth_bias DOUBLE PRECISION NOT NULL,
+    loss_resistance DOUBLE PRECISION NOT NULL,
+    duel_strength DOUBLE PRECISION NOT NULL,
+    parent_a TEXT NOT NULL,
+    parent_b TEXT NOT NULL,
+    born_at DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX idx_piglets_chat_owner ON piglets(chat_id, owner);
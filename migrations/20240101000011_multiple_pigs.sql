@@ -0,0 +1,13 @@
+-- A player may keep several pigs per chat; exactly one of them is active
+ALTER TABLE pigs ADD COLUMN active BOOLEAN NOT NULL DEFAULT TRUE;
+ALTER TABLE pigs DROP CONSTRAINT pigs_chat_id_user_id_key;
+
+CREATE UNIQUE INDEX idx_pigs_active ON pigs(chat_id, user_id) WHERE active;
+
+-- Weight history follows the pig, not its owner
+ALTER TABLE pig_weight_history ADD COLUMN pig_id INTEGER REFERENCES pigs(id) ON DELETE CASCADE;
+
+UPDATE pig_weight_history h SET pig_id = p.id
+FROM pigs p WHERE p.chat_id = h.chat_id AND p.user_id = h.user_id;
+
+CREATE INDEX idx_weight_history_pig_id ON pig_weight_history(pig_id, recorded_at);
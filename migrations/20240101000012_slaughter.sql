@@ -0,0 +1,22 @@
+-- Slaughtered pigs, kept for /graveyard
+CREATE TABLE pig_graveyard (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    name TEXT NOT NULL,
+    owner_name TEXT NOT NULL,
+    weight INTEGER NOT NULL,
+    salo INTEGER NOT NULL,
+    prestige INTEGER NOT NULL,
+    slaughtered_at DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX idx_graveyard_chat_time ON pig_graveyard(chat_id, slaughtered_at DESC);
+
+-- Prestige points a player earned by slaughtering pigs
+CREATE TABLE prestige (
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    points INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (chat_id, user_id)
+);
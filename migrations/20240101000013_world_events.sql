@@ -0,0 +1,12 @@
+-- Chat-wide random events; one-off events end as soon as they start
+CREATE TABLE world_events (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    kind TEXT NOT NULL,
+    started_at DOUBLE PRECISION NOT NULL,
+    ends_at DOUBLE PRECISION NOT NULL
+);
+
+CREATE INDEX idx_world_events_chat_end ON world_events(chat_id, ends_at);
+
+ALTER TABLE chat_settings ADD COLUMN events_disabled BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,22 @@
+-- Loot players put up for sale in their chat, kept after the sale as price history
+CREATE TABLE market_listings (
+    id SERIAL PRIMARY KEY,
+    chat_id BIGINT NOT NULL,
+    seller BIGINT NOT NULL,
+    seller_name TEXT NOT NULL,
+    loot_uuid UUID NOT NULL,
+    item_name TEXT NOT NULL,
+    item_icon TEXT NOT NULL,
+    price INTEGER NOT NULL,
+    status TEXT NOT NULL DEFAULT 'active',
+    created_at DOUBLE PRECISION NOT NULL,
+    expires_at DOUBLE PRECISION NOT NULL,
+    buyer BIGINT,
+    buyer_name TEXT,
+    fee INTEGER NOT NULL DEFAULT 0,
+    sold_at DOUBLE PRECISION
+);
+
+-- An item can only be on sale once
+CREATE UNIQUE INDEX idx_market_listings_loot ON market_listings(loot_uuid) WHERE status = 'active';
+CREATE INDEX idx_market_listings_chat_status ON market_listings(chat_id, status, created_at);
@@ -0,0 +1,2 @@
+-- Keys of modules switched off in a chat
+ALTER TABLE chat_settings ADD COLUMN disabled_modules TEXT[] NOT NULL DEFAULT '{}';
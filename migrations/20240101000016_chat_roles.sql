@@ -0,0 +1,8 @@
+-- Custom roles chat admins grant to members
+CREATE TABLE chat_roles (
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    user_name TEXT NOT NULL,
+    role TEXT NOT NULL,
+    PRIMARY KEY (chat_id, user_id, role)
+);
@@ -0,0 +1,36 @@
+-- Salo and buildings belong to the player and are shared by every pig in their pigsty
+CREATE TABLE farms (
+    chat_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    salo INTEGER NOT NULL DEFAULT 0,
+    barn INTEGER NOT NULL DEFAULT 0,
+    pigsty INTEGER NOT NULL DEFAULT 0,
+    vetclinic INTEGER NOT NULL DEFAULT 0,
+    vet_last_pickup DOUBLE PRECISION NOT NULL DEFAULT 0,
+    biolab INTEGER NOT NULL DEFAULT 0,
+    butchery INTEGER NOT NULL DEFAULT 0,
+    factory INTEGER NOT NULL DEFAULT 0,
+    warehouse INTEGER NOT NULL DEFAULT 0,
+    institute INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (chat_id, user_id)
+);
+
+-- Salo was split across a player's pigs; each building keeps its best level
+INSERT INTO farms (chat_id, user_id, salo, barn, pigsty, vetclinic, vet_last_pickup,
+                   biolab, butchery, factory, warehouse, institute)
+SELECT chat_id, user_id, SUM(salo), MAX(barn), MAX(pigsty), MAX(vetclinic), MAX(vet_last_pickup),
+       MAX(biolab), MAX(butchery), MAX(factory), MAX(warehouse), MAX(institute)
+FROM pigs
+GROUP BY chat_id, user_id;
+
+ALTER TABLE pigs
+    DROP COLUMN salo,
+    DROP COLUMN barn,
+    DROP COLUMN pigsty,
+    DROP COLUMN vetclinic,
+    DROP COLUMN vet_last_pickup,
+    DROP COLUMN biolab,
+    DROP COLUMN butchery,
+    DROP COLUMN factory,
+    DROP COLUMN warehouse,
+    DROP COLUMN institute;
@@ -0,0 +1,3 @@
+-- Rarity is always written by the game; the old '{}' default could not be read back
+UPDATE loot SET rarity = '"common"' WHERE rarity = '{}';
+ALTER TABLE loot ALTER COLUMN rarity DROP DEFAULT;
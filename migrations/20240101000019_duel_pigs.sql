@@ -0,0 +1,14 @@
+-- Duels remember which of the players' pigs fought, so records follow the pig
+ALTER TABLE duels
+    ADD COLUMN challenger_pig_id INTEGER REFERENCES pigs(id) ON DELETE SET NULL,
+    ADD COLUMN target_pig_id INTEGER REFERENCES pigs(id) ON DELETE SET NULL;
+
+-- Older duels were fought by the players' active pigs
+UPDATE duels d SET challenger_pig_id = p.id
+FROM pigs p WHERE p.chat_id = d.chat_id AND p.user_id = d.challenger_id AND p.active;
+
+UPDATE duels d SET target_pig_id = p.id
+FROM pigs p WHERE p.chat_id = d.chat_id AND p.user_id = d.target_id AND p.active;
+
+CREATE INDEX idx_duels_challenger_pig ON duels(challenger_pig_id);
+CREATE INDEX idx_duels_target_pig ON duels(target_pig_id);
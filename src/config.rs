@@ -10,6 +10,7 @@ pub struct GPTConfig {
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
 pub struct GameConfig {
     #[serde(rename = "FEED_DELAY")]
     pub feed_delay: u64,
@@ -27,12 +28,229 @@ pub struct GameConfig {
     pub base_pills_chance: f64,
     #[serde(rename = "BASE_PILLS_CHANCE_GROW")]
     pub base_pills_chance_grow: f64,
+    #[serde(rename = "POISON_COST")]
+    pub poison_cost: i32,
+    #[serde(rename = "POISON_CHANCE")]
+    pub poison_chance: f64,
+    #[serde(rename = "VET_CURE_COST")]
+    pub vet_cure_cost: i32,
+    #[serde(rename = "LOOT_CHANCE")]
+    pub loot_chance: f64,
+    #[serde(rename = "TRADE_OFFER_TTL")]
+    pub trade_offer_ttl: u64,
+    #[serde(rename = "DUEL_TTL")]
+    pub duel_ttl: u64,
+    /// Season length in days.
+    #[serde(rename = "SEASON_LENGTH")]
+    pub season_length: u64,
+    /// Share of weight a pig keeps into the next season; 0 resets it.
+    #[serde(rename = "SEASON_WEIGHT_KEEP")]
+    pub season_weight_keep: f64,
+    /// Salo paid for each completed daily quest.
+    #[serde(rename = "QUEST_REWARD")]
+    pub quest_reward: i32,
+    #[serde(rename = "BREED_COST")]
+    pub breed_cost: i32,
+    /// Hours both parents rest after breeding.
+    #[serde(rename = "BREED_DELAY")]
+    pub breed_delay: u64,
+    /// Lightest pig that can be slaughtered.
+    #[serde(rename = "SLAUGHTER_MIN_WEIGHT")]
+    pub slaughter_min_weight: i32,
+    /// Salo per kilogram of a slaughtered pig, before the butchery bonus.
+    #[serde(rename = "SLAUGHTER_SALO_PER_KG")]
+    pub slaughter_salo_per_kg: f64,
+    /// Extra grow ceiling per prestige point.
+    #[serde(rename = "PRESTIGE_BONUS")]
+    pub prestige_bonus: f64,
+    /// Share of each market sale that is burned instead of paid to the seller.
+    #[serde(rename = "MARKET_FEE")]
+    pub market_fee: f64,
+    /// Hours a market listing stays up.
+    #[serde(rename = "MARKET_LISTING_TTL")]
+    pub market_listing_ttl: u64,
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            base_growth: 0.1,
+            rank_factor: 0.5,
+            weight_factor: 0.05,
+            feed_delay: 4,
+            salo_delay: 8,
+            max_items: 15,
+            base_pills_chance: 0.33,
+            base_pills_chance_grow: 0.75,
+            poison_cost: 30,
+            poison_chance: 0.5,
+            vet_cure_cost: 50,
+            loot_chance: 0.15,
+            trade_offer_ttl: 10,
+            duel_ttl: 10,
+            season_length: 30,
+            season_weight_keep: 0.0,
+            quest_reward: 30,
+            breed_cost: 100,
+            breed_delay: 24,
+            slaughter_min_weight: 100,
+            slaughter_salo_per_kg: 0.5,
+            prestige_bonus: 0.05,
+            market_fee: 0.1,
+            market_listing_ttl: 48,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct BuildingConfig {
+    #[serde(rename = "NAME")]
+    pub name: String,
+    #[serde(rename = "ICON")]
+    pub icon: String,
+    #[serde(rename = "DESCRIPTION")]
+    pub description: String,
+    #[serde(rename = "BASE_COST")]
+    pub base_cost: i32,
+    #[serde(rename = "COST_MULTIPLIER")]
+    pub cost_multiplier: f64,
+    #[serde(rename = "MAX_LEVEL")]
+    pub max_level: i32,
+    #[serde(rename = "EFFECT_PER_LEVEL")]
+    pub effect_per_level: f64,
+}
+
+impl BuildingConfig {
+    fn new(name: &str, icon: &str, description: &str, base_cost: i32, cost_multiplier: f64, max_level: i32, effect_per_level: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            icon: icon.to_string(),
+            description: description.to_string(),
+            base_cost,
+            cost_multiplier,
+            max_level,
+            effect_per_level,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct BuildingsConfig {
+    pub barn: BuildingConfig,
+    pub pigsty: BuildingConfig,
+    pub vetclinic: BuildingConfig,
+    pub biolab: BuildingConfig,
+    pub butchery: BuildingConfig,
+    pub factory: BuildingConfig,
+    pub warehouse: BuildingConfig,
+    pub institute: BuildingConfig,
+}
+
+impl Default for BuildingsConfig {
+    fn default() -> Self {
+        Self {
+            barn: BuildingConfig::new("Сарай", "🏠", "к потолку прироста", 50, 1.6, 10, 0.05),
+            pigsty: BuildingConfig::new("Свинарник", "🛖", "мест в свинарнике", 200, 2.0, 5, 1.0),
+            vetclinic: BuildingConfig::new("Ветклиника", "🏥", "сала в день с клиники", 100, 1.7, 10, 5.0),
+            biolab: BuildingConfig::new("Биолаборатория", "🧪", "к шансу удачной таблетки", 120, 1.7, 10, 0.03),
+            butchery: BuildingConfig::new("Бойня", "🔪", "к выходу сала", 80, 1.6, 10, 0.25),
+            factory: BuildingConfig::new("Фабрика", "🏭", "к скорости производства сала", 150, 1.8, 10, 0.05),
+            warehouse: BuildingConfig::new("Склад", "📦", "мест для предметов", 100, 1.5, 10, 2.0),
+            institute: BuildingConfig::new("Институт", "🎓", "к шансу найти таблетку", 150, 1.7, 10, 0.02),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct WorldEventConfig {
+    #[serde(rename = "NAME")]
+    pub name: String,
+    #[serde(rename = "ICON")]
+    pub icon: String,
+    #[serde(rename = "DESCRIPTION")]
+    pub description: String,
+    /// Chance to start in a chat on each roll.
+    #[serde(rename = "CHANCE")]
+    pub chance: f64,
+    /// Minutes the event lasts; 0 for events that act once.
+    #[serde(rename = "DURATION")]
+    pub duration: u64,
+    #[serde(rename = "EFFECT")]
+    pub effect: f64,
+}
+
+impl WorldEventConfig {
+    fn new(name: &str, icon: &str, description: &str, chance: f64, duration: u64, effect: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            icon: icon.to_string(),
+            description: description.to_string(),
+            chance,
+            duration,
+            effect,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct WorldEventsConfig {
+    /// Minutes between event rolls; 0 disables world events.
+    #[serde(rename = "INTERVAL")]
+    pub interval: u64,
+    pub swine_flu: WorldEventConfig,
+    pub harvest: WorldEventConfig,
+    pub wolf_raid: WorldEventConfig,
+}
+
+impl Default for WorldEventsConfig {
+    fn default() -> Self {
+        Self {
+            interval: 60,
+            swine_flu: WorldEventConfig::new("Свиной грипп", "🤒", "все свиньи теряют часть веса", 0.01, 0, 0.1),
+            harvest: WorldEventConfig::new("Урожай", "🌾", "прирост при кормёжке умножается", 0.02, 60, 2.0),
+            wolf_raid: WorldEventConfig::new("Набег волков", "🐺", "волки тащат сало у свиней без сарая", 0.02, 0, 0.25),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+#[serde(default)]
+pub struct PermissionsConfig {
+    /// Telegram user ids of the bot owners, who pass every permission check.
+    #[serde(rename = "SUPERUSERS")]
+    pub superusers: Vec<u64>,
+    /// Seconds a chat member's admin status is trusted before Telegram is asked again.
+    #[serde(rename = "ROLE_CACHE_TTL")]
+    pub role_cache_ttl: u64,
+}
+
+impl PermissionsConfig {
+    pub fn is_superuser(&self, user_id: u64) -> bool {
+        self.superusers.contains(&user_id)
+    }
+}
+
+impl Default for PermissionsConfig {
+    fn default() -> Self {
+        Self {
+            superusers: Vec::new(),
+            role_cache_ttl: 300,
+        }
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct Config {
     pub gpt: GPTConfig,
     pub game: GameConfig,
+    #[serde(default)]
+    pub buildings: BuildingsConfig,
+    #[serde(default)]
+    pub events: WorldEventsConfig,
+    #[serde(default)]
+    pub permissions: PermissionsConfig,
     pub database_url: Option<String>,
 }
 
@@ -53,16 +271,10 @@ impl Config {
                     llm_api_url: std::env::var("LLM_API_URL").unwrap_or_default(),
                     llm_api_token: std::env::var("LLM_API_TOKEN").unwrap_or_default(),
                 },
-                game: GameConfig {
-                    base_growth: 0.1,
-                    rank_factor: 0.5,
-                    weight_factor: 0.05,
-                    feed_delay: 4,
-                    salo_delay: 8,
-                    max_items: 15,
-                    base_pills_chance: 0.33,
-                    base_pills_chance_grow: 0.75,
-                },
+                game: GameConfig::default(),
+                buildings: BuildingsConfig::default(),
+                events: WorldEventsConfig::default(),
+                permissions: PermissionsConfig::default(),
                 database_url: std::env::var("DATABASE_URL").ok(),
             }
         })
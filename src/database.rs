@@ -1,7 +1,10 @@
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use sqlx::types::Json;
 use uuid::Uuid;
 
+/// A pig joined with its owner's farm: salo and building levels belong to the
+/// player and are shared by every pig they keep in the chat.
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Pig {
     pub id: i32,
@@ -28,6 +31,37 @@ pub struct Pig {
     pub institute: i32,
 }
 
+/// A pig in its owner's pigsty, with whether it is the one commands act on.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct StablePig {
+    #[sqlx(flatten)]
+    pub pig: Pig,
+    pub active: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+    Epic,
+    Legendary,
+}
+
+/// Typed contents of `loot.base_stats`. Missing keys default to zero so old
+/// rows and sparse catalogue entries stay readable.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ItemStats {
+    /// Flat kilograms added to the grow ceiling.
+    pub grow_bonus: i32,
+    /// Fraction by which the worst possible loss is reduced.
+    pub loss_reduction: f64,
+    /// Fraction by which the feed cooldown is shortened.
+    pub cooldown_reduction: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct Loot {
     pub id: i32,
@@ -39,9 +73,288 @@ pub struct Loot {
     pub class_name: String,
     pub class_icon: String,
     pub weight: f64,
-    pub base_stats: serde_json::Value,
-    pub rarity: serde_json::Value,
+    pub base_stats: Json<ItemStats>,
+    pub rarity: Json<Rarity>,
     pub uuid: Uuid,
+    pub equipped: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct LootOffer {
+    pub id: i32,
+    pub chat_id: i64,
+    pub from_user: i64,
+    pub to_user: i64,
+    pub offered_uuid: Uuid,
+    pub requested_uuid: Option<Uuid>,
+    pub from_accepted: bool,
+    pub to_accepted: bool,
+    pub status: String,
+    pub expires_at: f64,
+}
+
+/// Why a loot offer could not be carried out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OfferFailure {
+    /// The offer is no longer pending or has expired.
+    Closed,
+    /// One of the items changed hands or disappeared meanwhile.
+    ItemMissing,
+    /// The recipient of a gift has no free inventory slot.
+    InventoryFull,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct WeightRecord {
+    pub weight: i32,
+    pub growth: i32,
+    pub recorded_at: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Duel {
+    pub id: i32,
+    pub chat_id: i64,
+    pub challenger_id: i64,
+    pub target_id: i64,
+    /// `None` once the pig is slaughtered.
+    pub challenger_pig_id: Option<i32>,
+    pub target_pig_id: Option<i32>,
+    pub wager: i32,
+    pub status: String,
+    pub winner_id: Option<i64>,
+    pub transferred: i32,
+    pub expires_at: f64,
+    pub resolved_at: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct DuelStats {
+    pub wins: i64,
+    pub losses: i64,
+    pub weight_won: i64,
+    pub weight_lost: i64,
+}
+
+/// Leaderboard ordering for /top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderboardSort {
+    Weight,
+    Salo,
+    Growth,
+    Wins,
+}
+
+impl LeaderboardSort {
+    pub const ALL: [LeaderboardSort; 4] = [
+        LeaderboardSort::Weight,
+        LeaderboardSort::Salo,
+        LeaderboardSort::Growth,
+        LeaderboardSort::Wins,
+    ];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            LeaderboardSort::Weight => "weight",
+            LeaderboardSort::Salo => "salo",
+            LeaderboardSort::Growth => "growth",
+            LeaderboardSort::Wins => "wins",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|sort| sort.key() == key)
+    }
+
+    /// Score column of pig `p` on its owner's farm `f`; `w.since` is the start
+    /// of the growth window.
+    fn score_sql(self) -> &'static str {
+        match self {
+            LeaderboardSort::Weight => "p.weight::BIGINT",
+            LeaderboardSort::Salo => "f.salo::BIGINT",
+            LeaderboardSort::Growth => {
+                "COALESCE((SELECT SUM(h.growth) FROM pig_weight_history h
+                  WHERE h.pig_id = p.id AND h.recorded_at >= w.since), 0)::BIGINT"
+            }
+            LeaderboardSort::Wins => {
+                "(SELECT COUNT(*) FROM duels d
+                  WHERE d.chat_id = p.chat_id AND d.status = 'finished' AND d.winner_id = p.user_id)::BIGINT"
+            }
+        }
+    }
+
+    /// A page of a chat's leaderboard, shared by /top and the season snapshot.
+    /// Binds the chat, the start of the growth window, the limit and the offset.
+    fn leaderboard_sql(self) -> String {
+        format!(
+            "SELECT p.user_id, p.name, p.owner_name, {} AS score
+             FROM pigs p JOIN farms f USING (chat_id, user_id)
+             CROSS JOIN (SELECT $2::DOUBLE PRECISION AS since) w
+             WHERE p.chat_id = $1 AND p.active
+             ORDER BY score DESC, p.weight DESC, p.id
+             LIMIT $3 OFFSET $4",
+            self.score_sql()
+        )
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LeaderboardEntry {
+    pub user_id: i64,
+    pub name: String,
+    pub owner_name: String,
+    pub score: i64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Season {
+    pub id: i32,
+    pub chat_id: i64,
+    pub number: i32,
+    pub started_at: f64,
+    pub ended_at: Option<f64>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SeasonStanding {
+    pub position: i32,
+    pub pig_name: String,
+    pub owner_name: String,
+    pub weight: i32,
+}
+
+/// Inherited modifiers of a pig, each a fraction such as `0.1` for +10%.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct PigTraits {
+    pub growth_bias: f64,
+    pub loss_resistance: f64,
+    pub duel_strength: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Piglet {
+    pub id: i32,
+    pub name: String,
+    #[sqlx(flatten)]
+    pub traits: PigTraits,
+    pub parent_a: String,
+    pub parent_b: String,
+}
+
+/// A slaughtered pig resting in the chat's graveyard.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct Grave {
+    pub name: String,
+    pub owner_name: String,
+    pub weight: i32,
+    pub salo: i32,
+    /// Owner's prestige right after the slaughter.
+    pub prestige: i32,
+    pub slaughtered_at: f64,
+}
+
+/// Limits a breeding is checked against.
+#[derive(Debug, Clone, Copy)]
+pub struct BreedTerms {
+    /// Salo the initiator pays.
+    pub cost: i32,
+    /// Seconds both parents must rest between breedings.
+    pub cooldown_secs: f64,
+    /// Piglets the initiator's stable holds.
+    pub max_piglets: i64,
+}
+
+/// Why two pigs could not breed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreedFailure {
+    /// One of the parents no longer exists.
+    PigMissing,
+    /// One of the parents bred too recently.
+    Cooldown,
+    /// The initiator cannot pay the breeding cost.
+    NotEnoughSalo,
+    /// The initiator's stable has no free place.
+    StableFull,
+}
+
+/// Why a piglet could not be raised.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RaiseFailure {
+    /// The piglet already left the stable.
+    PigletMissing,
+    /// The owner's pigsty has no free place.
+    PigstyFull,
+}
+
+/// An item on a chat's market. Sold listings are kept as sale history.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct MarketListing {
+    pub id: i32,
+    pub chat_id: i64,
+    pub seller: i64,
+    pub seller_name: String,
+    pub loot_uuid: Uuid,
+    pub item_name: String,
+    pub item_icon: String,
+    pub price: i32,
+    pub expires_at: f64,
+    pub buyer_name: Option<String>,
+    /// Part of the price burned by the market.
+    pub fee: i32,
+    pub sold_at: Option<f64>,
+}
+
+/// Why a market purchase did not go through.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MarketFailure {
+    /// The listing was sold, withdrawn or has expired.
+    Closed,
+    /// The seller no longer owns the item; the listing is withdrawn.
+    ItemMissing,
+    /// The buyer's pig cannot pay the price.
+    NotEnoughSalo,
+    /// The buyer has no free inventory slot.
+    InventoryFull,
+}
+
+/// Counters behind achievement progress that are not stored on the pig itself.
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct AchievementCounters {
+    pub feeds: i64,
+    pub max_loss: i64,
+    pub duel_wins: i64,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct ChatSettings {
+    pub global_opt_out: bool,
+    pub utc_offset: i32,
+    pub events_disabled: bool,
+    /// Keys of modules switched off in the chat.
+    pub disabled_modules: Vec<String>,
+}
+
+/// A custom role a chat admin granted to a member.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ChatRole {
+    pub user_name: String,
+    pub role: String,
+}
+
+/// A world event that started in a chat.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct WorldEventRecord {
+    pub kind: String,
+    pub ends_at: f64,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DailyQuest {
+    pub kind: String,
+    pub target: i32,
+    pub progress: i32,
+    pub reward: i32,
+    pub completed: bool,
 }
 
 pub struct Database {
@@ -58,24 +371,109 @@ impl Database {
         sqlx::migrate!("./migrations").run(&self.pool).await
     }
 
-    pub async fn get_chat_pigs_ranked(&self, chat_id: i64) -> Result<Vec<Pig>, sqlx::Error> {
-        let result = sqlx::query_as::<_, Pig>(
-            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
-             salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
-             avatar_url, biolab, butchery, pills, factory, warehouse, institute
-             FROM pigs WHERE chat_id = $1 ORDER BY weight DESC"
+    pub async fn get_leaderboard(
+        &self,
+        chat_id: i64,
+        sort: LeaderboardSort,
+        since: f64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+        sqlx::query_as::<_, LeaderboardEntry>(&sort.leaderboard_sql())
+            .bind(chat_id)
+            .bind(since)
+            .bind(limit)
+            .bind(offset)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    /// 1-based position of a player's pig on the leaderboard, with its entry.
+    pub async fn get_leaderboard_position(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        sort: LeaderboardSort,
+        since: f64,
+    ) -> Result<Option<(i64, LeaderboardEntry)>, sqlx::Error> {
+        let query = format!(
+            "SELECT position, user_id, name, owner_name, score FROM (
+                SELECT p.user_id, p.name, p.owner_name, {score} AS score,
+                       ROW_NUMBER() OVER (ORDER BY {score} DESC, p.weight DESC, p.id) AS position
+                FROM pigs p JOIN farms f USING (chat_id, user_id)
+                CROSS JOIN (SELECT $2::DOUBLE PRECISION AS since) w
+                WHERE p.chat_id = $1 AND p.active
+             ) ranked WHERE user_id = $3",
+            score = sort.score_sql()
+        );
+        let row: Option<(i64, i64, String, String, i64)> = sqlx::query_as(&query)
+            .bind(chat_id)
+            .bind(since)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        Ok(row.map(|(position, user_id, name, owner_name, score)| {
+            (position, LeaderboardEntry { user_id, name, owner_name, score })
+        }))
+    }
+
+    /// Heaviest pigs across every chat that has not opted out of the global top.
+    pub async fn get_global_leaderboard(&self, limit: i64) -> Result<Vec<LeaderboardEntry>, sqlx::Error> {
+        sqlx::query_as::<_, LeaderboardEntry>(
+            "SELECT p.user_id, p.name, p.owner_name, p.weight::BIGINT AS score
+             FROM pigs p
+             WHERE p.active AND NOT EXISTS (
+                SELECT 1 FROM chat_settings s WHERE s.chat_id = p.chat_id AND s.global_opt_out
+             )
+             ORDER BY p.weight DESC, p.id
+             LIMIT $1",
         )
-        .bind(chat_id)
+        .bind(limit)
         .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Global position of a player's heaviest visible pig.
+    pub async fn get_global_position(&self, user_id: i64) -> Result<Option<(i64, LeaderboardEntry)>, sqlx::Error> {
+        let row: Option<(i64, i64, String, String, i64)> = sqlx::query_as(
+            "SELECT position, user_id, name, owner_name, score FROM (
+                SELECT p.user_id, p.name, p.owner_name, p.weight::BIGINT AS score,
+                       ROW_NUMBER() OVER (ORDER BY p.weight DESC, p.id) AS position
+                FROM pigs p
+                WHERE p.active AND NOT EXISTS (
+                    SELECT 1 FROM chat_settings s WHERE s.chat_id = p.chat_id AND s.global_opt_out
+                )
+             ) ranked WHERE user_id = $1
+             ORDER BY position LIMIT 1",
+        )
+        .bind(user_id)
+        .fetch_optional(&self.pool)
         .await?;
 
-        Ok(result)
+        Ok(row.map(|(position, user_id, name, owner_name, score)| {
+            (position, LeaderboardEntry { user_id, name, owner_name, score })
+        }))
+    }
 
+    /// Every pig a player owns, in all chats, heaviest first.
+    pub async fn get_user_pigs(&self, user_id: i64) -> Result<Vec<Pig>, sqlx::Error> {
+        sqlx::query_as::<_, Pig>(
+            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE user_id = $1 AND active
+             ORDER BY weight DESC",
+        )
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
     }
 
     pub async fn get_chat_total_players(&self, chat_id: i64) -> Result<i32, sqlx::Error> {
         let count: (i64,) = sqlx::query_as(
-            "SELECT COUNT(*) FROM pigs WHERE chat_id = $1",
+            "SELECT COUNT(*) FROM pigs WHERE chat_id = $1 AND active",
         )
         .bind(chat_id)
         .fetch_one(&self.pool)
@@ -88,7 +486,7 @@ impl Database {
         let result: Option<(i64,)> = sqlx::query_as(
             "SELECT rank FROM (
             SELECT user_id, ROW_NUMBER() OVER (ORDER BY weight DESC) as rank
-            FROM pigs WHERE chat_id = $1
+            FROM pigs WHERE chat_id = $1 AND active
             ) ranked WHERE user_id = $2"
         )
         .bind(chat_id)
@@ -103,7 +501,8 @@ impl Database {
             "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
              salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
              avatar_url, biolab, butchery, pills, factory, warehouse, institute
-             FROM pigs WHERE chat_id = $1 AND user_id = $2",
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE chat_id = $1 AND user_id = $2 AND active",
         )
         .bind(chat_id)
         .bind(user_id)
@@ -111,51 +510,56 @@ impl Database {
         .await
     }
 
-    pub async fn create_pig(&self, pig: &Pig) -> Result<Pig, sqlx::Error> {
+    /// Any pig, active or not, by its id.
+    pub async fn get_pig_by_id(&self, pig_id: i32) -> Result<Option<Pig>, sqlx::Error> {
         sqlx::query_as::<_, Pig>(
-            "INSERT INTO pigs (chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
-                              salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
-                              avatar_url, biolab, butchery, pills, factory, warehouse, institute)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)
-             RETURNING id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
-                       salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
-                       avatar_url, biolab, butchery, pills, factory, warehouse, institute"
+            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+             salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+             avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE id = $1",
+        )
+        .bind(pig_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Inserts the pig and, for a new player, their farm.
+    pub async fn create_pig(&self, pig: &Pig) -> Result<Pig, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO farms (chat_id, user_id, salo, barn, pigsty, vetclinic, vet_last_pickup,
+                               biolab, butchery, factory, warehouse, institute)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             ON CONFLICT (chat_id, user_id) DO NOTHING",
         )
         .bind(pig.chat_id)
         .bind(pig.user_id)
-        .bind(pig.weight)
-        .bind(&pig.name)
-        .bind(pig.last_feed)
-        .bind(pig.last_salo)
-        .bind(&pig.owner_name)
         .bind(pig.salo)
-        .bind(pig.poisoned)
         .bind(pig.barn)
         .bind(pig.pigsty)
         .bind(pig.vetclinic)
         .bind(pig.vet_last_pickup)
-        .bind(pig.last_weight)
-        .bind(&pig.avatar_url)
         .bind(pig.biolab)
         .bind(pig.butchery)
-        .bind(pig.pills)
         .bind(pig.factory)
         .bind(pig.warehouse)
         .bind(pig.institute)
-        .fetch_one(&self.pool)
-        .await
-    }
+        .execute(&mut *tx)
+        .await?;
 
-    pub async fn update_pig(&self, pig: &Pig) -> Result<Pig, sqlx::Error> {
-        sqlx::query_as::<_, Pig>(
-            "UPDATE pigs SET weight = $3, name = $4, last_feed = $5, last_salo = $6, owner_name = $7,
-                            salo = $8, poisoned = $9, barn = $10, pigsty = $11, vetclinic = $12,
-                            vet_last_pickup = $13, last_weight = $14, avatar_url = $15, biolab = $16,
-                            butchery = $17, pills = $18, factory = $19, warehouse = $20, institute = $21
-             WHERE chat_id = $1 AND user_id = $2
-             RETURNING id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
-                       salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
-                       avatar_url, biolab, butchery, pills, factory, warehouse, institute"
+        let pig = sqlx::query_as::<_, Pig>(
+            "WITH p AS (
+                INSERT INTO pigs (chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                                  poisoned, last_weight, avatar_url, pills)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM p JOIN farms USING (chat_id, user_id)"
         )
         .bind(pig.chat_id)
         .bind(pig.user_id)
@@ -164,92 +568,1938 @@ impl Database {
         .bind(pig.last_feed)
         .bind(pig.last_salo)
         .bind(&pig.owner_name)
-        .bind(pig.salo)
         .bind(pig.poisoned)
-        .bind(pig.barn)
-        .bind(pig.pigsty)
-        .bind(pig.vetclinic)
-        .bind(pig.vet_last_pickup)
         .bind(pig.last_weight)
         .bind(&pig.avatar_url)
-        .bind(pig.biolab)
-        .bind(pig.butchery)
         .bind(pig.pills)
-        .bind(pig.factory)
-        .bind(pig.warehouse)
-        .bind(pig.institute)
-        .fetch_one(&self.pool)
-        .await
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(pig)
     }
 
-    pub async fn update_pig_name(&self, chat_id: i64, user_id: i64, new_name: &str) -> Result<Pig, sqlx::Error> {
-        sqlx::query_as::<_, Pig>(
-            "UPDATE pigs SET name = $1 WHERE chat_id = $2 AND user_id = $3
-            RETURNING id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
-            salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
-            avatar_url, biolab, butchery, pills, factory, warehouse, institute",
+    /// Every pig a player keeps in a chat, oldest first.
+    pub async fn get_stable_pigs(&self, chat_id: i64, user_id: i64) -> Result<Vec<StablePig>, sqlx::Error> {
+        sqlx::query_as::<_, StablePig>(
+            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute, active
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE chat_id = $1 AND user_id = $2
+             ORDER BY id",
         )
-        .bind(new_name)
         .bind(chat_id)
         .bind(user_id)
-        .fetch_one(&self.pool)
+        .fetch_all(&self.pool)
         .await
     }
 
-    // Loot operations
-    pub async fn get_user_loot(
+    /// Adds an inactive pig to the player's pigsty. Returns `None` when the
+    /// player already keeps `max_pigs` pigs in this chat.
+    pub async fn add_stable_pig(
         &self,
         chat_id: i64,
         user_id: i64,
-    ) -> Result<Vec<Loot>, sqlx::Error> {
-        sqlx::query_as::<_, Loot>(
-            "SELECT id, chat_id, owner, name, icon, description, class_name, class_icon,
-                    weight, base_stats, rarity, uuid
-             FROM loot WHERE chat_id = $1 AND owner = $2",
+        name: &str,
+        owner_name: &str,
+        max_pigs: i64,
+        now: f64,
+    ) -> Result<Option<Pig>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let Some(pig) = Self::insert_stable_pig(&mut tx, chat_id, user_id, name, owner_name, max_pigs, now).await? else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        tx.commit().await?;
+        Ok(Some(pig))
+    }
+
+    /// Moves a piglet out of the stable into its owner's pigsty as an inactive
+    /// pig that keeps the piglet's genes.
+    pub async fn raise_piglet(
+        &self,
+        piglet_id: i32,
+        owner: i64,
+        owner_name: &str,
+        max_pigs: i64,
+        now: f64,
+    ) -> Result<Result<Pig, RaiseFailure>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let piglet: Option<(i64, String, f64, f64, f64)> = sqlx::query_as(
+            "DELETE FROM piglets WHERE id = $1 AND owner = $2
+             RETURNING chat_id, name, growth_bias, loss_resistance, duel_strength",
+        )
+        .bind(piglet_id)
+        .bind(owner)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((chat_id, name, growth_bias, loss_resistance, duel_strength)) = piglet else {
+            tx.rollback().await?;
+            return Ok(Err(RaiseFailure::PigletMissing));
+        };
+
+        let Some(pig) = Self::insert_stable_pig(&mut tx, chat_id, owner, &name, owner_name, max_pigs, now).await? else {
+            tx.rollback().await?;
+            return Ok(Err(RaiseFailure::PigstyFull));
+        };
+
+        sqlx::query(
+            "INSERT INTO pig_genetics (pig_id, growth_bias, loss_resistance, duel_strength)
+             VALUES ($1, $2, $3, $4)",
+        )
+        .bind(pig.id)
+        .bind(growth_bias)
+        .bind(loss_resistance)
+        .bind(duel_strength)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Ok(pig))
+    }
+
+    /// Inserts an inactive pig unless the player already keeps `max_pigs`
+    /// pigs in the chat.
+    async fn insert_stable_pig(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        chat_id: i64,
+        user_id: i64,
+        name: &str,
+        owner_name: &str,
+        max_pigs: i64,
+        now: f64,
+    ) -> Result<Option<Pig>, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO farms (chat_id, user_id, vet_last_pickup) VALUES ($1, $2, $3)
+             ON CONFLICT (chat_id, user_id) DO NOTHING",
         )
         .bind(chat_id)
         .bind(user_id)
-        .fetch_all(&self.pool)
+        .bind(now)
+        .execute(&mut **tx)
+        .await?;
+
+        // Lock the player's farm so concurrent adds count each other's pigs.
+        sqlx::query("SELECT 1 FROM farms WHERE chat_id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(chat_id)
+            .bind(user_id)
+            .execute(&mut **tx)
+            .await?;
+
+        let (owned,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM pigs WHERE chat_id = $1 AND user_id = $2")
+            .bind(chat_id)
+            .bind(user_id)
+            .fetch_one(&mut **tx)
+            .await?;
+
+        if owned >= max_pigs {
+            return Ok(None);
+        }
+
+        sqlx::query_as::<_, Pig>(
+            "WITH p AS (
+                INSERT INTO pigs (chat_id, user_id, name, owner_name, last_salo, active)
+                VALUES ($1, $2, $3, $4, $5, FALSE)
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM p JOIN farms USING (chat_id, user_id)",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(name)
+        .bind(owner_name)
+        .bind(now)
+        .fetch_one(&mut **tx)
         .await
+        .map(Some)
     }
 
-    pub async fn add_loot(&self, loot: &Loot) -> Result<Loot, sqlx::Error> {
-        sqlx::query_as::<_, Loot>(
-            "INSERT INTO loot (chat_id, owner, name, icon, description, class_name, class_icon,
-                              weight, base_stats, rarity, uuid)
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-             RETURNING id, chat_id, owner, name, icon, description, class_name, class_icon,
-                       weight, base_stats, rarity, uuid",
+    /// Makes `pig_id` the player's active pig. Returns `None` if the pig is not
+    /// theirs.
+    pub async fn set_active_pig(&self, chat_id: i64, user_id: i64, pig_id: i32) -> Result<Option<Pig>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE pigs SET active = FALSE WHERE chat_id = $1 AND user_id = $2 AND active AND id <> $3")
+            .bind(chat_id)
+            .bind(user_id)
+            .bind(pig_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let pig = sqlx::query_as::<_, Pig>(
+            "WITH p AS (
+                UPDATE pigs SET active = TRUE
+                WHERE id = $3 AND chat_id = $1 AND user_id = $2
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM p JOIN farms USING (chat_id, user_id)",
         )
-        .bind(loot.chat_id)
-        .bind(loot.owner)
-        .bind(&loot.name)
-        .bind(&loot.icon)
-        .bind(&loot.description)
-        .bind(&loot.class_name)
-        .bind(&loot.class_icon)
-        .bind(loot.weight)
-        .bind(&loot.base_stats)
-        .bind(&loot.rarity)
-        .bind(loot.uuid)
-        .fetch_one(&self.pool)
-        .await
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(pig_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if pig.is_none() {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        tx.commit().await?;
+        Ok(pig)
     }
 
-    pub async fn find_pig_by_name(
+    /// Applies the result of a feed as deltas so that concurrent salo or
+    /// building updates on the same row are not overwritten, and records the
+    /// new weight in `pig_weight_history`.
+    pub async fn apply_feed(
         &self,
         chat_id: i64,
-        name: &str,
-    ) -> Result<Vec<Pig>, sqlx::Error> {
-        let search_pattern = format!("%{}%", name);
+        user_id: i64,
+        growth: i32,
+        pills_delta: i32,
+        poison: bool,
+        fed_at: f64,
+    ) -> Result<Pig, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let pig = sqlx::query_as::<_, Pig>(
+            "WITH p AS (
+                UPDATE pigs SET last_weight = weight, weight = GREATEST(weight + $3, 1),
+                                pills = GREATEST(pills + $4, 0), poisoned = poisoned OR $5
+                WHERE chat_id = $1 AND user_id = $2 AND active
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM p JOIN farms USING (chat_id, user_id)"
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(growth)
+        .bind(pills_delta)
+        .bind(poison)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO pig_weight_history (chat_id, user_id, pig_id, weight, growth, recorded_at)
+             VALUES ($1, $2, $3, $4, $5, $6)",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(pig.id)
+        .bind(pig.weight)
+        .bind(pig.weight - pig.last_weight)
+        .bind(fed_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(pig)
+    }
+
+    /// The pig's most recent `limit` weight records, oldest first.
+    pub async fn get_weight_history(&self, pig_id: i32, limit: i64) -> Result<Vec<WeightRecord>, sqlx::Error> {
+        sqlx::query_as::<_, WeightRecord>(
+            "SELECT weight, growth, recorded_at FROM (
+                SELECT weight, growth, recorded_at FROM pig_weight_history
+                WHERE pig_id = $1
+                ORDER BY recorded_at DESC LIMIT $2
+             ) recent ORDER BY recorded_at",
+        )
+        .bind(pig_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn update_pig_name(&self, chat_id: i64, user_id: i64, new_name: &str) -> Result<Pig, sqlx::Error> {
         sqlx::query_as::<_, Pig>(
-            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+            "WITH p AS (
+                UPDATE pigs SET name = $1 WHERE chat_id = $2 AND user_id = $3 AND active
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
                     salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
                     avatar_url, biolab, butchery, pills, factory, warehouse, institute
-             FROM pigs WHERE chat_id = $1 AND name ILIKE $2",
+             FROM p JOIN farms USING (chat_id, user_id)",
         )
+        .bind(new_name)
         .bind(chat_id)
-        .bind(search_pattern)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Atomically stamps `last_feed` with `now` if the feed cooldown has elapsed.
+    /// Returns `false` when the pig is still on cooldown, so concurrent feeds
+    /// cannot both pass the check.
+    pub async fn try_claim_feed(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        now: f64,
+        cooldown_secs: f64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE pigs SET last_feed = $3
+             WHERE chat_id = $1 AND user_id = $2 AND active AND last_feed <= $3 - $4",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(now)
+        .bind(cooldown_secs)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Credits produced salo to the farm and moves the pig's `last_salo`
+    /// forward, but only if nobody collected since `expected_last_salo` was read.
+    pub async fn collect_salo(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        amount: i32,
+        expected_last_salo: f64,
+        new_last_salo: f64,
+    ) -> Result<Option<Pig>, sqlx::Error> {
+        sqlx::query_as::<_, Pig>(
+            "WITH p AS (
+                UPDATE pigs SET last_salo = $5
+                WHERE chat_id = $1 AND user_id = $2 AND active AND last_salo = $4
+                RETURNING *
+             ), f AS (
+                UPDATE farms SET salo = salo + $3
+                WHERE (chat_id, user_id) IN (SELECT chat_id, user_id FROM p)
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM p JOIN f USING (chat_id, user_id)",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(amount)
+        .bind(expected_last_salo)
+        .bind(new_last_salo)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Charges `cost` salo and raises `column` by one level, provided the farm
+    /// still has the salo and the building is still at `current_level`.
+    /// `column` must be one of the fixed building column names. A newly built
+    /// vet clinic starts earning at `now`.
+    pub async fn upgrade_building(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        column: &str,
+        current_level: i32,
+        cost: i32,
+        now: f64,
+    ) -> Result<Option<Pig>, sqlx::Error> {
+        let opens_clinic = column == "vetclinic" && current_level == 0;
+        let pickup = if opens_clinic { "$5" } else { "vet_last_pickup" };
+        let query = format!(
+            "WITH f AS (
+                UPDATE farms SET salo = salo - $3, {column} = {column} + 1, vet_last_pickup = {pickup}
+                WHERE chat_id = $1 AND user_id = $2 AND salo >= $3 AND {column} = $4
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN f USING (chat_id, user_id)
+             WHERE active"
+        );
+
+        sqlx::query_as::<_, Pig>(&query)
+            .bind(chat_id)
+            .bind(user_id)
+            .bind(cost)
+            .bind(current_level)
+            .bind(now)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Charges the attacker `cost` salo and, if `success`, poisons the target,
+    /// all in one transaction. Returns `None` when the attacker cannot pay.
+    pub async fn poison_pig(
+        &self,
+        chat_id: i64,
+        attacker_id: i64,
+        target_id: i64,
+        cost: i32,
+        success: bool,
+    ) -> Result<Option<bool>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let paid = sqlx::query(
+            "UPDATE farms SET salo = salo - $3 WHERE chat_id = $1 AND user_id = $2 AND salo >= $3",
+        )
+        .bind(chat_id)
+        .bind(attacker_id)
+        .bind(cost)
+        .execute(&mut *tx)
+        .await?;
+
+        if paid.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let poisoned = if success {
+            sqlx::query(
+                "UPDATE pigs SET poisoned = TRUE WHERE chat_id = $1 AND user_id = $2 AND active AND NOT poisoned",
+            )
+            .bind(chat_id)
+            .bind(target_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected()
+                > 0
+        } else {
+            false
+        };
+
+        tx.commit().await?;
+        Ok(Some(poisoned))
+    }
+
+    /// Clears the poisoned flag for `cost` salo. Returns `None` if the pig is
+    /// not poisoned or cannot pay.
+    pub async fn cure_pig(&self, chat_id: i64, user_id: i64, cost: i32) -> Result<Option<Pig>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let cured = sqlx::query(
+            "UPDATE pigs SET poisoned = FALSE WHERE chat_id = $1 AND user_id = $2 AND active AND poisoned",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .execute(&mut *tx)
+        .await?;
+
+        let paid = sqlx::query(
+            "UPDATE farms SET salo = salo - $3 WHERE chat_id = $1 AND user_id = $2 AND salo >= $3",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(cost)
+        .execute(&mut *tx)
+        .await?;
+
+        if cured.rows_affected() == 0 || paid.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let pig = sqlx::query_as::<_, Pig>(
+            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE chat_id = $1 AND user_id = $2 AND active",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(pig))
+    }
+
+    /// Credits vet clinic income, guarded on `vet_last_pickup` like `collect_salo`.
+    pub async fn collect_vet_income(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        amount: i32,
+        expected_last_pickup: f64,
+        new_last_pickup: f64,
+    ) -> Result<Option<Pig>, sqlx::Error> {
+        sqlx::query_as::<_, Pig>(
+            "WITH f AS (
+                UPDATE farms SET salo = salo + $3, vet_last_pickup = $5
+                WHERE chat_id = $1 AND user_id = $2 AND vet_last_pickup = $4
+                RETURNING *
+             )
+             SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN f USING (chat_id, user_id)
+             WHERE active",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(amount)
+        .bind(expected_last_pickup)
+        .bind(new_last_pickup)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    // Loot operations
+    pub async fn get_user_loot(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+    ) -> Result<Vec<Loot>, sqlx::Error> {
+        sqlx::query_as::<_, Loot>(
+            "SELECT id, chat_id, owner, name, icon, description, class_name, class_icon,
+                    weight, base_stats, rarity, uuid, equipped
+             FROM loot WHERE chat_id = $1 AND owner = $2 ORDER BY id",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_equipped_loot(&self, chat_id: i64, user_id: i64) -> Result<Vec<Loot>, sqlx::Error> {
+        sqlx::query_as::<_, Loot>(
+            "SELECT id, chat_id, owner, name, icon, description, class_name, class_icon,
+                    weight, base_stats, rarity, uuid, equipped
+             FROM loot WHERE chat_id = $1 AND owner = $2 AND equipped ORDER BY id",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Equips or unequips one of the owner's items. Equipping first frees the
+    /// slot taken by another item of the same class. Returns `false` if the
+    /// item does not belong to the owner.
+    pub async fn set_loot_equipped(
+        &self,
+        chat_id: i64,
+        owner: i64,
+        loot_id: i32,
+        equipped: bool,
+    ) -> Result<bool, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        if equipped {
+            sqlx::query(
+                "UPDATE loot SET equipped = FALSE
+                 WHERE chat_id = $1 AND owner = $2 AND equipped AND id <> $3
+                   AND class_name = (SELECT class_name FROM loot WHERE id = $3 AND chat_id = $1 AND owner = $2)",
+            )
+            .bind(chat_id)
+            .bind(owner)
+            .bind(loot_id)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        let updated = sqlx::query(
+            "UPDATE loot SET equipped = $4 WHERE id = $3 AND chat_id = $1 AND owner = $2",
+        )
+        .bind(chat_id)
+        .bind(owner)
+        .bind(loot_id)
+        .bind(equipped)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(updated.rows_affected() > 0)
+    }
+
+    /// Inserts `loot` unless its owner already holds `max_items` items in
+    /// this chat, in which case `None` is returned.
+    pub async fn add_loot(&self, loot: &Loot, max_items: i64) -> Result<Option<Loot>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        if Self::count_loot_locked(&mut tx, loot.chat_id, loot.owner).await? >= max_items {
+            tx.rollback().await?;
+            return Ok(None);
+        }
+
+        let added = sqlx::query_as::<_, Loot>(
+            "INSERT INTO loot (chat_id, owner, name, icon, description, class_name, class_icon,
+                              weight, base_stats, rarity, uuid)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+             RETURNING id, chat_id, owner, name, icon, description, class_name, class_icon,
+                       weight, base_stats, rarity, uuid, equipped",
+        )
+        .bind(loot.chat_id)
+        .bind(loot.owner)
+        .bind(&loot.name)
+        .bind(&loot.icon)
+        .bind(&loot.description)
+        .bind(&loot.class_name)
+        .bind(&loot.class_icon)
+        .bind(loot.weight)
+        .bind(&loot.base_stats)
+        .bind(loot.rarity)
+        .bind(loot.uuid)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(added))
+    }
+
+    /// Counts the owner's items in the chat after locking their farm row, so
+    /// concurrent additions to one inventory are checked one at a time.
+    async fn count_loot_locked(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        chat_id: i64,
+        owner: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query("SELECT 1 FROM farms WHERE chat_id = $1 AND user_id = $2 FOR UPDATE")
+            .bind(chat_id)
+            .bind(owner)
+            .execute(&mut **tx)
+            .await?;
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM loot WHERE chat_id = $1 AND owner = $2")
+            .bind(chat_id)
+            .bind(owner)
+            .fetch_one(&mut **tx)
+            .await?;
+        Ok(count)
+    }
+
+    pub async fn find_pig_by_name(
+        &self,
+        chat_id: i64,
+        name: &str,
+    ) -> Result<Vec<Pig>, sqlx::Error> {
+        let search_pattern = format!("%{}%", name);
+        sqlx::query_as::<_, Pig>(
+            "SELECT id, chat_id, user_id, weight, name, last_feed, last_salo, owner_name,
+                    salo, poisoned, barn, pigsty, vetclinic, vet_last_pickup, last_weight,
+                    avatar_url, biolab, butchery, pills, factory, warehouse, institute
+             FROM pigs JOIN farms USING (chat_id, user_id)
+             WHERE chat_id = $1 AND active AND name ILIKE $2",
+        )
+        .bind(chat_id)
+        .bind(search_pattern)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // Loot offers
+    pub async fn create_loot_offer(
+        &self,
+        chat_id: i64,
+        from_user: i64,
+        to_user: i64,
+        offered_uuid: Uuid,
+        requested_uuid: Option<Uuid>,
+        expires_at: f64,
+    ) -> Result<LootOffer, sqlx::Error> {
+        sqlx::query_as::<_, LootOffer>(
+            "INSERT INTO loot_offers (chat_id, from_user, to_user, offered_uuid, requested_uuid, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6)
+             RETURNING id, chat_id, from_user, to_user, offered_uuid, requested_uuid,
+                       from_accepted, to_accepted, status, expires_at",
+        )
+        .bind(chat_id)
+        .bind(from_user)
+        .bind(to_user)
+        .bind(offered_uuid)
+        .bind(requested_uuid)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_loot_offer(&self, offer_id: i32) -> Result<Option<LootOffer>, sqlx::Error> {
+        sqlx::query_as::<_, LootOffer>(
+            "SELECT id, chat_id, from_user, to_user, offered_uuid, requested_uuid,
+                    from_accepted, to_accepted, status, expires_at
+             FROM loot_offers WHERE id = $1",
+        )
+        .bind(offer_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    pub async fn get_loot_by_uuid(&self, uuid: Uuid) -> Result<Option<Loot>, sqlx::Error> {
+        sqlx::query_as::<_, Loot>(
+            "SELECT id, chat_id, owner, name, icon, description, class_name, class_icon,
+                    weight, base_stats, rarity, uuid, equipped
+             FROM loot WHERE uuid = $1",
+        )
+        .bind(uuid)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Records `user_id`'s acceptance of a pending, unexpired offer they are part of.
+    pub async fn accept_loot_offer(&self, offer_id: i32, user_id: i64, now: f64) -> Result<Option<LootOffer>, sqlx::Error> {
+        sqlx::query_as::<_, LootOffer>(
+            "UPDATE loot_offers
+             SET from_accepted = from_accepted OR from_user = $2,
+                 to_accepted = to_accepted OR to_user = $2
+             WHERE id = $1 AND status = 'pending' AND expires_at > $3 AND $2 IN (from_user, to_user)
+             RETURNING id, chat_id, from_user, to_user, offered_uuid, requested_uuid,
+                       from_accepted, to_accepted, status, expires_at",
+        )
+        .bind(offer_id)
+        .bind(user_id)
+        .bind(now)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Moves a still-pending offer to a final `status` such as `declined` or `expired`.
+    pub async fn close_loot_offer(&self, offer_id: i32, status: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE loot_offers SET status = $2 WHERE id = $1 AND status = 'pending'",
+        )
+        .bind(offer_id)
+        .bind(status)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Carries out an offer both sides accepted: items swap owners and the
+    /// offer is closed in one transaction, so an item can never end up with
+    /// both players.
+    pub async fn execute_loot_offer(
+        &self,
+        offer_id: i32,
+        now: f64,
+        recipient_max_items: i64,
+    ) -> Result<Result<(), OfferFailure>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let offer = sqlx::query_as::<_, LootOffer>(
+            "SELECT id, chat_id, from_user, to_user, offered_uuid, requested_uuid,
+                    from_accepted, to_accepted, status, expires_at
+             FROM loot_offers
+             WHERE id = $1 AND status = 'pending' AND expires_at > $2 AND from_accepted AND to_accepted
+             FOR UPDATE",
+        )
+        .bind(offer_id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(offer) = offer else {
+            tx.rollback().await?;
+            return Ok(Err(OfferFailure::Closed));
+        };
+
+        if offer.requested_uuid.is_none() {
+            let count = Self::count_loot_locked(&mut tx, offer.chat_id, offer.to_user).await?;
+            if count >= recipient_max_items {
+                tx.rollback().await?;
+                return Ok(Err(OfferFailure::InventoryFull));
+            }
+        }
+
+        let mut transfers = vec![(offer.offered_uuid, offer.from_user, offer.to_user)];
+        if let Some(requested) = offer.requested_uuid {
+            transfers.push((requested, offer.to_user, offer.from_user));
+        }
+
+        for (uuid, from, to) in transfers {
+            if !Self::transfer_loot(&mut tx, offer.chat_id, uuid, from, to).await? {
+                tx.rollback().await?;
+                return Ok(Err(OfferFailure::ItemMissing));
+            }
+        }
+
+        sqlx::query("UPDATE loot_offers SET status = 'accepted' WHERE id = $1")
+            .bind(offer.id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Ok(()))
+    }
+
+    /// Hands the item with `uuid` from `from` to `to`, unequipping it. Returns
+    /// `false` if `from` no longer owns it.
+    async fn transfer_loot(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        chat_id: i64,
+        uuid: Uuid,
+        from: i64,
+        to: i64,
+    ) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE loot SET owner = $4, equipped = FALSE WHERE uuid = $1 AND chat_id = $2 AND owner = $3",
+        )
+        .bind(uuid)
+        .bind(chat_id)
+        .bind(from)
+        .bind(to)
+        .execute(&mut **tx)
+        .await?;
+
+        Ok(result.rows_affected() == 1)
+    }
+
+    // Duels
+    pub async fn create_duel(
+        &self,
+        challenger: &Pig,
+        target: &Pig,
+        wager: i32,
+        expires_at: f64,
+    ) -> Result<Duel, sqlx::Error> {
+        sqlx::query_as::<_, Duel>(
+            "INSERT INTO duels (chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, expires_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7)
+             RETURNING id, chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, status, winner_id,
+                       transferred, expires_at, resolved_at",
+        )
+        .bind(challenger.chat_id)
+        .bind(challenger.user_id)
+        .bind(target.user_id)
+        .bind(challenger.id)
+        .bind(target.id)
+        .bind(wager)
+        .bind(expires_at)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_duel(&self, duel_id: i32) -> Result<Option<Duel>, sqlx::Error> {
+        sqlx::query_as::<_, Duel>(
+            "SELECT id, chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, status, winner_id,
+                    transferred, expires_at, resolved_at
+             FROM duels WHERE id = $1",
+        )
+        .bind(duel_id)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Moves a still-pending duel to a final `status` such as `declined` or `expired`.
+    pub async fn close_duel(&self, duel_id: i32, status: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE duels SET status = $2 WHERE id = $1 AND status = 'pending'",
+        )
+        .bind(duel_id)
+        .bind(status)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Settles a pending duel in one transaction: the losing pig gives up to
+    /// the wager (never dropping below 1 kg), the winning pig receives what
+    /// was taken. Returns the settled duel, or `None` if it was no longer pending.
+    pub async fn resolve_duel(
+        &self,
+        duel_id: i32,
+        winner: &Pig,
+        loser: &Pig,
+        now: f64,
+    ) -> Result<Option<Duel>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let duel = sqlx::query_as::<_, Duel>(
+            "SELECT id, chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, status, winner_id,
+                    transferred, expires_at, resolved_at
+             FROM duels WHERE id = $1 AND status = 'pending' AND expires_at > $2
+             FOR UPDATE",
+        )
+        .bind(duel_id)
+        .bind(now)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(duel) = duel else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+
+        let loser_weight: Option<(i32,)> = sqlx::query_as("SELECT weight FROM pigs WHERE id = $1 FOR UPDATE")
+            .bind(loser.id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        let taken = loser_weight.map(|(weight,)| duel.wager.min(weight - 1).max(0)).unwrap_or(0);
+
+        for (pig_id, delta) in [(loser.id, -taken), (winner.id, taken)] {
+            sqlx::query("UPDATE pigs SET weight = weight + $2 WHERE id = $1")
+                .bind(pig_id)
+                .bind(delta)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        let settled = sqlx::query_as::<_, Duel>(
+            "UPDATE duels SET status = 'finished', winner_id = $2, transferred = $3, resolved_at = $4
+             WHERE id = $1
+             RETURNING id, chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, status, winner_id,
+                       transferred, expires_at, resolved_at",
+        )
+        .bind(duel.id)
+        .bind(winner.user_id)
+        .bind(taken)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(settled))
+    }
+
+    /// Win/loss record of one pig, not of its owner's pigs combined.
+    pub async fn get_duel_stats(&self, pig_id: i32) -> Result<DuelStats, sqlx::Error> {
+        sqlx::query_as::<_, DuelStats>(
+            "SELECT COUNT(*) FILTER (WHERE won) AS wins,
+                    COUNT(*) FILTER (WHERE NOT won) AS losses,
+                    COALESCE(SUM(transferred) FILTER (WHERE won), 0) AS weight_won,
+                    COALESCE(SUM(transferred) FILTER (WHERE NOT won), 0) AS weight_lost
+             FROM (
+                SELECT transferred,
+                       winner_id = CASE WHEN challenger_pig_id = $1 THEN challenger_id ELSE target_id END AS won
+                FROM duels
+                WHERE status = 'finished' AND $1 IN (challenger_pig_id, target_pig_id)
+             ) d",
+        )
+        .bind(pig_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    pub async fn get_recent_duels(&self, pig_id: i32, limit: i64) -> Result<Vec<Duel>, sqlx::Error> {
+        sqlx::query_as::<_, Duel>(
+            "SELECT id, chat_id, challenger_id, target_id, challenger_pig_id, target_pig_id, wager, status, winner_id,
+                    transferred, expires_at, resolved_at
+             FROM duels
+             WHERE status = 'finished' AND $1 IN (challenger_pig_id, target_pig_id)
+             ORDER BY resolved_at DESC LIMIT $2",
+        )
+        .bind(pig_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // Chat settings
+
+    pub async fn get_chat_settings(&self, chat_id: i64) -> Result<ChatSettings, sqlx::Error> {
+        let settings = sqlx::query_as::<_, ChatSettings>(
+            "SELECT global_opt_out, utc_offset, events_disabled, disabled_modules FROM chat_settings WHERE chat_id = $1",
+        )
+        .bind(chat_id)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(settings.unwrap_or_default())
+    }
+
+    /// Those of `chat_ids` that opted out of the global top.
+    pub async fn get_opted_out_chats(&self, chat_ids: &[i64]) -> Result<Vec<i64>, sqlx::Error> {
+        sqlx::query_scalar("SELECT chat_id FROM chat_settings WHERE chat_id = ANY($1) AND global_opt_out")
+            .bind(chat_ids)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    pub async fn set_global_opt_out(&self, chat_id: i64, opt_out: bool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO chat_settings (chat_id, global_opt_out) VALUES ($1, $2)
+             ON CONFLICT (chat_id) DO UPDATE SET global_opt_out = EXCLUDED.global_opt_out",
+        )
+        .bind(chat_id)
+        .bind(opt_out)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn set_events_disabled(&self, chat_id: i64, disabled: bool) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO chat_settings (chat_id, events_disabled) VALUES ($1, $2)
+             ON CONFLICT (chat_id) DO UPDATE SET events_disabled = EXCLUDED.events_disabled",
+        )
+        .bind(chat_id)
+        .bind(disabled)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Switches a module off or back on in a chat and returns the modules now off.
+    pub async fn set_module_disabled(&self, chat_id: i64, module: &str, disabled: bool) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            "INSERT INTO chat_settings (chat_id, disabled_modules)
+             VALUES ($1, CASE WHEN $3 THEN ARRAY[$2::TEXT] ELSE '{}' END)
+             ON CONFLICT (chat_id) DO UPDATE SET disabled_modules = CASE
+                WHEN $3 THEN array_append(array_remove(chat_settings.disabled_modules, $2::TEXT), $2::TEXT)
+                ELSE array_remove(chat_settings.disabled_modules, $2::TEXT)
+             END
+             RETURNING disabled_modules",
+        )
+        .bind(chat_id)
+        .bind(module)
+        .bind(disabled)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    // Chat roles
+
+    /// Returns `false` if the member already had the role.
+    pub async fn grant_chat_role(&self, chat_id: i64, user_id: i64, user_name: &str, role: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO chat_roles (chat_id, user_id, user_name, role) VALUES ($1, $2, $3, $4)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(user_name)
+        .bind(role)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// Returns `false` if the member did not have the role.
+    pub async fn revoke_chat_role(&self, chat_id: i64, user_id: i64, role: &str) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query("DELETE FROM chat_roles WHERE chat_id = $1 AND user_id = $2 AND role = $3")
+            .bind(chat_id)
+            .bind(user_id)
+            .bind(role)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    pub async fn has_chat_role(&self, chat_id: i64, user_id: i64, role: &str) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM chat_roles WHERE chat_id = $1 AND user_id = $2 AND role = $3)")
+            .bind(chat_id)
+            .bind(user_id)
+            .bind(role)
+            .fetch_one(&self.pool)
+            .await
+    }
+
+    pub async fn get_chat_roles(&self, chat_id: i64) -> Result<Vec<ChatRole>, sqlx::Error> {
+        sqlx::query_as::<_, ChatRole>("SELECT user_name, role FROM chat_roles WHERE chat_id = $1 ORDER BY role, user_name")
+            .bind(chat_id)
+            .fetch_all(&self.pool)
+            .await
+    }
+
+    // Seasons
+
+    /// Opens the first season of a chat if it never had one and returns the running season.
+    pub async fn get_or_start_season(&self, chat_id: i64, now: f64) -> Result<Season, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO seasons (chat_id, number, started_at)
+             SELECT $1, 1, $2
+             WHERE NOT EXISTS (SELECT 1 FROM seasons WHERE chat_id = $1)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(chat_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_as::<_, Season>(
+            "SELECT id, chat_id, number, started_at, ended_at
+             FROM seasons WHERE chat_id = $1 AND ended_at IS NULL",
+        )
+        .bind(chat_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Opens the first season of every chat with pigs that has none yet,
+    /// skipping chats that switched `module` off.
+    pub async fn start_missing_seasons(&self, now: f64, module: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO seasons (chat_id, number, started_at)
+             SELECT DISTINCT p.chat_id, 1, $1 FROM pigs p
+             WHERE NOT EXISTS (SELECT 1 FROM seasons s WHERE s.chat_id = p.chat_id)
+               AND NOT EXISTS (
+                SELECT 1 FROM chat_settings cs WHERE cs.chat_id = p.chat_id AND $2 = ANY(cs.disabled_modules)
+               )
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(now)
+        .bind(module)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Running seasons that started at or before `cutoff`, except in chats
+    /// that switched `module` off.
+    pub async fn get_expired_seasons(&self, cutoff: f64, module: &str) -> Result<Vec<Season>, sqlx::Error> {
+        sqlx::query_as::<_, Season>(
+            "SELECT id, chat_id, number, started_at, ended_at
+             FROM seasons s WHERE ended_at IS NULL AND started_at <= $1
+             AND NOT EXISTS (
+                SELECT 1 FROM chat_settings cs WHERE cs.chat_id = s.chat_id AND $2 = ANY(cs.disabled_modules)
+             )",
+        )
+        .bind(cutoff)
+        .bind(module)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Archives the top `archive_size` pigs of a running season, ranked by the
+    /// same weight leaderboard /top pages through, scales every pig's weight
+    /// by `weight_keep` and opens the next season. Returns `None` if the
+    /// season was already closed.
+    pub async fn end_season(
+        &self,
+        season_id: i32,
+        now: f64,
+        weight_keep: f64,
+        archive_size: i64,
+    ) -> Result<Option<(Season, Vec<SeasonStanding>)>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let season = sqlx::query_as::<_, Season>(
+            "SELECT id, chat_id, number, started_at, ended_at
+             FROM seasons WHERE id = $1 AND ended_at IS NULL FOR UPDATE",
+        )
+        .bind(season_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(season) = season else {
+            return Ok(None);
+        };
+
+        let ranked = sqlx::query_as::<_, LeaderboardEntry>(&LeaderboardSort::Weight.leaderboard_sql())
+            .bind(season.chat_id)
+            .bind(now)
+            .bind(archive_size)
+            .bind(0_i64)
+            .fetch_all(&mut *tx)
+            .await?;
+
+        let mut standings = Vec::with_capacity(ranked.len());
+        for (position, entry) in (1..).zip(&ranked) {
+            let standing = sqlx::query_as::<_, SeasonStanding>(
+                "INSERT INTO season_standings (season_id, position, user_id, pig_name, owner_name, weight)
+                 VALUES ($1, $2, $3, $4, $5, $6)
+                 RETURNING position, pig_name, owner_name, weight",
+            )
+            .bind(season.id)
+            .bind(position)
+            .bind(entry.user_id)
+            .bind(&entry.name)
+            .bind(&entry.owner_name)
+            .bind(entry.score as i32)
+            .fetch_one(&mut *tx)
+            .await?;
+            standings.push(standing);
+        }
+
+        sqlx::query(
+            "UPDATE pigs SET weight = GREATEST(FLOOR(weight * $2)::INTEGER, 1), last_weight = weight
+             WHERE chat_id = $1",
+        )
+        .bind(season.chat_id)
+        .bind(weight_keep)
+        .execute(&mut *tx)
+        .await?;
+
+        let ended = sqlx::query_as::<_, Season>(
+            "UPDATE seasons SET ended_at = $2 WHERE id = $1
+             RETURNING id, chat_id, number, started_at, ended_at",
+        )
+        .bind(season.id)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query("INSERT INTO seasons (chat_id, number, started_at) VALUES ($1, $2, $3)")
+            .bind(season.chat_id)
+            .bind(season.number + 1)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+
+        Ok(Some((ended, standings)))
+    }
+
+    pub async fn count_finished_seasons(&self, chat_id: i64) -> Result<i64, sqlx::Error> {
+        let count: (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM seasons WHERE chat_id = $1 AND ended_at IS NOT NULL",
+        )
+        .bind(chat_id)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count.0)
+    }
+
+    /// Finished seasons of a chat, newest first.
+    pub async fn get_finished_seasons(&self, chat_id: i64, limit: i64, offset: i64) -> Result<Vec<Season>, sqlx::Error> {
+        sqlx::query_as::<_, Season>(
+            "SELECT id, chat_id, number, started_at, ended_at
+             FROM seasons WHERE chat_id = $1 AND ended_at IS NOT NULL
+             ORDER BY number DESC LIMIT $2 OFFSET $3",
+        )
+        .bind(chat_id)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn get_season_standings(&self, season_id: i32, limit: i64) -> Result<Vec<SeasonStanding>, sqlx::Error> {
+        sqlx::query_as::<_, SeasonStanding>(
+            "SELECT position, pig_name, owner_name, weight
+             FROM season_standings WHERE season_id = $1
+             ORDER BY position LIMIT $2",
+        )
+        .bind(season_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// `(season number, position)` of every podium finish of a player in a chat, newest first.
+    pub async fn get_season_titles(&self, chat_id: i64, user_id: i64, podium: i32) -> Result<Vec<(i32, i32)>, sqlx::Error> {
+        sqlx::query_as(
+            "SELECT s.number, st.position
+             FROM season_standings st JOIN seasons s ON s.id = st.season_id
+             WHERE s.chat_id = $1 AND st.user_id = $2 AND st.position <= $3
+             ORDER BY s.number DESC",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(podium)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // Achievements
+
+    pub async fn get_achievement_counters(&self, chat_id: i64, user_id: i64) -> Result<AchievementCounters, sqlx::Error> {
+        sqlx::query_as::<_, AchievementCounters>(
+            "SELECT
+                (SELECT COUNT(*) FROM pig_weight_history
+                 WHERE chat_id = $1 AND user_id = $2) AS feeds,
+                (SELECT COALESCE(-MIN(growth), 0)::BIGINT FROM pig_weight_history
+                 WHERE chat_id = $1 AND user_id = $2) AS max_loss,
+                (SELECT COUNT(*) FROM duels
+                 WHERE chat_id = $1 AND status = 'finished' AND winner_id = $2) AS duel_wins",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_one(&self.pool)
+        .await
+    }
+
+    /// Keys of a pig's unlocked achievements.
+    pub async fn get_unlocked_achievements(&self, chat_id: i64, user_id: i64) -> Result<Vec<String>, sqlx::Error> {
+        let rows: Vec<(String,)> = sqlx::query_as(
+            "SELECT key FROM pig_achievements WHERE chat_id = $1 AND user_id = $2 ORDER BY unlocked_at",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    /// Returns `false` if the achievement was already unlocked, so it is announced once.
+    pub async fn unlock_achievement(&self, chat_id: i64, user_id: i64, key: &str, now: f64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO pig_achievements (chat_id, user_id, key, unlocked_at) VALUES ($1, $2, $3, $4)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(key)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    /// Records the chat's current heaviest pig, keeping `since` while the leader
+    /// stays the same, and returns `(user_id, since)`.
+    pub async fn update_chat_leader(&self, chat_id: i64, now: f64) -> Result<Option<(i64, f64)>, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO chat_leaders (chat_id, user_id, since)
+             SELECT chat_id, user_id, $2 FROM pigs WHERE chat_id = $1 AND active
+             ORDER BY weight DESC, id LIMIT 1
+             ON CONFLICT (chat_id) DO UPDATE SET user_id = EXCLUDED.user_id, since = EXCLUDED.since
+             WHERE chat_leaders.user_id <> EXCLUDED.user_id",
+        )
+        .bind(chat_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_as("SELECT user_id, since FROM chat_leaders WHERE chat_id = $1")
+            .bind(chat_id)
+            .fetch_optional(&self.pool)
+            .await
+    }
+
+    /// Re-ranks every chat at once and returns the leaders who have held first
+    /// place since `held_since` or earlier, except in chats that switched
+    /// `module` off.
+    pub async fn update_chat_leaders(&self, now: f64, held_since: f64, module: &str) -> Result<Vec<(i64, i64)>, sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO chat_leaders (chat_id, user_id, since)
+             SELECT DISTINCT ON (chat_id) chat_id, user_id, $1 FROM pigs WHERE active
+             ORDER BY chat_id, weight DESC, id
+             ON CONFLICT (chat_id) DO UPDATE SET user_id = EXCLUDED.user_id, since = EXCLUDED.since
+             WHERE chat_leaders.user_id <> EXCLUDED.user_id",
+        )
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_as(
+            "SELECT chat_id, user_id FROM chat_leaders l
+             WHERE since <= $1 AND NOT EXISTS (
+                SELECT 1 FROM chat_settings s WHERE s.chat_id = l.chat_id AND $2 = ANY(s.disabled_modules)
+             )",
+        )
+        .bind(held_since)
+        .bind(module)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn set_utc_offset(&self, chat_id: i64, utc_offset: i32) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            "INSERT INTO chat_settings (chat_id, utc_offset) VALUES ($1, $2)
+             ON CONFLICT (chat_id) DO UPDATE SET utc_offset = EXCLUDED.utc_offset",
+        )
+        .bind(chat_id)
+        .bind(utc_offset)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    // Daily quests
+
+    pub async fn get_daily_quests(&self, chat_id: i64, user_id: i64, day: i64) -> Result<Vec<DailyQuest>, sqlx::Error> {
+        sqlx::query_as::<_, DailyQuest>(
+            "SELECT kind, target, progress, reward, completed
+             FROM daily_quests WHERE chat_id = $1 AND user_id = $2 AND day = $3
+             ORDER BY id",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(day)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Stores the day's quests unless the player already has some for that day.
+    /// `quests` holds `(kind, target, reward)`.
+    pub async fn create_daily_quests(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        day: i64,
+        quests: &[(&str, i32, i32)],
+    ) -> Result<(), sqlx::Error> {
+        let kinds: Vec<String> = quests.iter().map(|q| q.0.to_string()).collect();
+        let targets: Vec<i32> = quests.iter().map(|q| q.1).collect();
+        let rewards: Vec<i32> = quests.iter().map(|q| q.2).collect();
+
+        sqlx::query(
+            "INSERT INTO daily_quests (chat_id, user_id, day, kind, target, reward)
+             SELECT $1, $2, $3, q.kind, q.target, q.reward
+             FROM UNNEST($4::TEXT[], $5::INTEGER[], $6::INTEGER[]) AS q(kind, target, reward)
+             WHERE NOT EXISTS (
+                SELECT 1 FROM daily_quests WHERE chat_id = $1 AND user_id = $2 AND day = $3
+             )
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(day)
+        .bind(kinds)
+        .bind(targets)
+        .bind(rewards)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Adds `amount` to the day's open quest of `kind` and pays the salo reward
+    /// if that completes it. Returns the quests completed by this call.
+    pub async fn advance_daily_quest(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        day: i64,
+        kind: &str,
+        amount: i32,
+    ) -> Result<Vec<DailyQuest>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let completed = sqlx::query_as::<_, DailyQuest>(
+            "UPDATE daily_quests
+             SET progress = LEAST(progress + $5, target), completed = progress + $5 >= target
+             WHERE chat_id = $1 AND user_id = $2 AND day = $3 AND kind = $4 AND NOT completed
+             RETURNING kind, target, progress, reward, completed",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(day)
+        .bind(kind)
+        .bind(amount)
+        .fetch_all(&mut *tx)
+        .await?
+        .into_iter()
+        .filter(|quest| quest.completed)
+        .collect::<Vec<_>>();
+
+        let reward: i32 = completed.iter().map(|quest| quest.reward).sum();
+        if reward > 0 {
+            sqlx::query("UPDATE farms SET salo = salo + $3 WHERE chat_id = $1 AND user_id = $2")
+                .bind(chat_id)
+                .bind(user_id)
+                .bind(reward)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(completed)
+    }
+
+    // Breeding
+
+    pub async fn get_pig_traits(&self, pig_id: i32) -> Result<PigTraits, sqlx::Error> {
+        let traits = sqlx::query_as::<_, PigTraits>(
+            "SELECT growth_bias, loss_resistance, duel_strength FROM pig_genetics WHERE pig_id = $1",
+        )
+        .bind(pig_id)
+        .fetch_optional(&self.pool)
+        .await?;
+        Ok(traits.unwrap_or_default())
+    }
+
+    /// Unix time the pig last bred, 0 if never.
+    pub async fn get_last_bred(&self, pig_id: i32) -> Result<f64, sqlx::Error> {
+        let row: Option<(f64,)> = sqlx::query_as("SELECT last_bred FROM pig_genetics WHERE pig_id = $1")
+            .bind(pig_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0).unwrap_or(0.0))
+    }
+
+    pub async fn get_piglets(&self, chat_id: i64, owner: i64) -> Result<Vec<Piglet>, sqlx::Error> {
+        sqlx::query_as::<_, Piglet>(
+            "SELECT id, name, growth_bias, loss_resistance, duel_strength, parent_a, parent_b
+             FROM piglets WHERE chat_id = $1 AND owner = $2 ORDER BY id",
+        )
+        .bind(chat_id)
+        .bind(owner)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Breeds the pigs `initiator_pig` and `partner_pig`: checks both cooldowns,
+    /// charges the initiator and puts the piglet in their stable.
+    pub async fn breed_pigs(
+        &self,
+        initiator_pig: i32,
+        partner_pig: i32,
+        piglet_name: &str,
+        traits: PigTraits,
+        terms: BreedTerms,
+        now: f64,
+    ) -> Result<Result<Piglet, BreedFailure>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query(
+            "INSERT INTO pig_genetics (pig_id) SELECT id FROM pigs WHERE id = ANY($1)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(vec![initiator_pig, partner_pig])
+        .execute(&mut *tx)
+        .await?;
+
+        let parents: Vec<(i32, i64, i64, String, i32, f64)> = sqlx::query_as(
+            "SELECT p.id, p.chat_id, p.user_id, p.name, f.salo, g.last_bred
+             FROM pigs p JOIN pig_genetics g ON g.pig_id = p.id
+             JOIN farms f USING (chat_id, user_id)
+             WHERE p.id = ANY($1)
+             ORDER BY p.id
+             FOR UPDATE",
+        )
+        .bind(vec![initiator_pig, partner_pig])
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let find = |id: i32| parents.iter().find(|parent| parent.0 == id);
+        let (Some(initiator), Some(partner)) = (find(initiator_pig), find(partner_pig)) else {
+            tx.rollback().await?;
+            return Ok(Err(BreedFailure::PigMissing));
+        };
+        let ready_before = now - terms.cooldown_secs;
+        if initiator.5 > ready_before || partner.5 > ready_before {
+            tx.rollback().await?;
+            return Ok(Err(BreedFailure::Cooldown));
+        }
+        if initiator.4 < terms.cost {
+            tx.rollback().await?;
+            return Ok(Err(BreedFailure::NotEnoughSalo));
+        }
+
+        let (count,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM piglets WHERE chat_id = $1 AND owner = $2")
+            .bind(initiator.1)
+            .bind(initiator.2)
+            .fetch_one(&mut *tx)
+            .await?;
+        if count >= terms.max_piglets {
+            tx.rollback().await?;
+            return Ok(Err(BreedFailure::StableFull));
+        }
+
+        sqlx::query("UPDATE farms SET salo = salo - $3 WHERE chat_id = $1 AND user_id = $2")
+            .bind(initiator.1)
+            .bind(initiator.2)
+            .bind(terms.cost)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE pig_genetics SET last_bred = $2 WHERE pig_id = ANY($1)")
+            .bind(vec![initiator_pig, partner_pig])
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+
+        let piglet = sqlx::query_as::<_, Piglet>(
+            "INSERT INTO piglets (chat_id, owner, name, growth_bias, loss_resistance, duel_strength, parent_a, parent_b, born_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING id, name, growth_bias, loss_resistance, duel_strength, parent_a, parent_b",
+        )
+        .bind(initiator.1)
+        .bind(initiator.2)
+        .bind(piglet_name)
+        .bind(traits.growth_bias)
+        .bind(traits.loss_resistance)
+        .bind(traits.duel_strength)
+        .bind(&initiator.3)
+        .bind(&partner.3)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Ok(piglet))
+    }
+
+    pub async fn get_prestige(&self, chat_id: i64, user_id: i64) -> Result<i32, sqlx::Error> {
+        let row: Option<(i32,)> = sqlx::query_as("SELECT points FROM prestige WHERE chat_id = $1 AND user_id = $2")
+            .bind(chat_id)
+            .bind(user_id)
+            .fetch_optional(&self.pool)
+            .await?;
+        Ok(row.map(|r| r.0).unwrap_or(0))
+    }
+
+    /// Buries a pig of at least `min_weight` kg: pays `salo_per_kg` for its
+    /// weight, grants the owner a prestige point and puts a newborn `new_name`
+    /// in its place. Returns `None` if the pig is gone or too light.
+    pub async fn slaughter_pig(
+        &self,
+        pig_id: i32,
+        min_weight: i32,
+        salo_per_kg: f64,
+        new_name: &str,
+        now: f64,
+    ) -> Result<Option<Grave>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let pig: Option<(i64, i64, String, String, i32)> = sqlx::query_as(
+            "SELECT chat_id, user_id, name, owner_name, weight FROM pigs
+             WHERE id = $1 AND weight >= $2 FOR UPDATE",
+        )
+        .bind(pig_id)
+        .bind(min_weight)
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some((chat_id, user_id, name, owner_name, weight)) = pig else {
+            tx.rollback().await?;
+            return Ok(None);
+        };
+        let salo = (weight as f64 * salo_per_kg).floor() as i32;
+
+        let (prestige,): (i32,) = sqlx::query_as(
+            "INSERT INTO prestige (chat_id, user_id, points) VALUES ($1, $2, 1)
+             ON CONFLICT (chat_id, user_id) DO UPDATE SET points = prestige.points + 1
+             RETURNING points",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let grave = sqlx::query_as::<_, Grave>(
+            "INSERT INTO pig_graveyard (chat_id, user_id, name, owner_name, weight, salo, prestige, slaughtered_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+             RETURNING name, owner_name, weight, salo, prestige, slaughtered_at",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(&name)
+        .bind(&owner_name)
+        .bind(weight)
+        .bind(salo)
+        .bind(prestige)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        // The newborn is a fresh row, so the dead pig's chart and genes stay
+        // behind; its feeds still count towards the owner's achievements.
+        sqlx::query("UPDATE pig_weight_history SET pig_id = NULL WHERE pig_id = $1")
+            .bind(pig_id)
+            .execute(&mut *tx)
+            .await?;
+
+        let (last_salo, avatar_url, pills, active): (f64, Option<String>, i32, bool) = sqlx::query_as(
+            "DELETE FROM pigs WHERE id = $1 RETURNING last_salo, avatar_url, pills, active",
+        )
+        .bind(pig_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            "INSERT INTO pigs (chat_id, user_id, name, owner_name, last_salo, avatar_url, pills, active)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(chat_id)
+        .bind(user_id)
+        .bind(new_name)
+        .bind(&owner_name)
+        .bind(last_salo)
+        .bind(avatar_url)
+        .bind(pills)
+        .bind(active)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE farms SET salo = salo + $3 WHERE chat_id = $1 AND user_id = $2")
+            .bind(chat_id)
+            .bind(user_id)
+            .bind(salo)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(grave))
+    }
+
+    /// The chat's most recently slaughtered pigs.
+    pub async fn get_graveyard(&self, chat_id: i64, limit: i64) -> Result<Vec<Grave>, sqlx::Error> {
+        sqlx::query_as::<_, Grave>(
+            "SELECT name, owner_name, weight, salo, prestige, slaughtered_at
+             FROM pig_graveyard WHERE chat_id = $1
+             ORDER BY slaughtered_at DESC LIMIT $2",
+        )
+        .bind(chat_id)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // World events
+
+    /// Chats with pigs that have turned neither world events nor `module` off.
+    pub async fn get_event_chats(&self, module: &str) -> Result<Vec<i64>, sqlx::Error> {
+        let rows: Vec<(i64,)> = sqlx::query_as(
+            "SELECT DISTINCT p.chat_id FROM pigs p
+             WHERE NOT EXISTS (
+                SELECT 1 FROM chat_settings s
+                WHERE s.chat_id = p.chat_id AND (s.events_disabled OR $1 = ANY(s.disabled_modules))
+             )",
+        )
+        .bind(module)
+        .fetch_all(&self.pool)
+        .await?;
+        Ok(rows.into_iter().map(|r| r.0).collect())
+    }
+
+    /// Records the start of an event unless one of the same kind is still
+    /// running in the chat. Returns whether it started.
+    pub async fn start_world_event(&self, chat_id: i64, kind: &str, now: f64, ends_at: f64) -> Result<bool, sqlx::Error> {
+        let result = sqlx::query(
+            "INSERT INTO world_events (chat_id, kind, started_at, ends_at)
+             SELECT $1, $2, $3, $4
+             WHERE NOT EXISTS (
+                SELECT 1 FROM world_events WHERE chat_id = $1 AND kind = $2 AND ends_at > $3
+             )",
+        )
+        .bind(chat_id)
+        .bind(kind)
+        .bind(now)
+        .bind(ends_at)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected() == 1)
+    }
+
+    pub async fn get_active_world_events(&self, chat_id: i64, now: f64) -> Result<Vec<WorldEventRecord>, sqlx::Error> {
+        sqlx::query_as::<_, WorldEventRecord>(
+            "SELECT kind, ends_at FROM world_events
+             WHERE chat_id = $1 AND ends_at > $2
+             ORDER BY ends_at",
+        )
+        .bind(chat_id)
+        .bind(now)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    /// Takes `share` of every pig's weight in the chat, keeping at least 1 kg.
+    pub async fn apply_weight_loss(&self, chat_id: i64, share: f64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            "UPDATE pigs SET weight = GREATEST(FLOOR(weight * (1 - $2))::INTEGER, 1)
+             WHERE chat_id = $1 AND weight > 1",
+        )
+        .bind(chat_id)
+        .bind(share)
+        .execute(&self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Takes `share` of the salo of every farm in the chat without a barn and
+    /// returns the victims' active pigs with what they lost.
+    pub async fn steal_unprotected_salo(&self, chat_id: i64, share: f64) -> Result<Vec<(String, i32)>, sqlx::Error> {
+        sqlx::query_as(
+            "UPDATE farms f SET salo = f.salo - loot.stolen
+             FROM (
+                SELECT user_id, FLOOR(salo * $2)::INTEGER AS stolen FROM farms
+                WHERE chat_id = $1 AND barn = 0 AND salo > 0
+                FOR UPDATE
+             ) loot, pigs p
+             WHERE f.chat_id = $1 AND f.user_id = loot.user_id AND loot.stolen > 0
+               AND p.chat_id = f.chat_id AND p.user_id = f.user_id AND p.active
+             RETURNING p.name, loot.stolen",
+        )
+        .bind(chat_id)
+        .bind(share)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    // Market
+
+    /// Puts one of the seller's items up for sale. Returns `None` if the item
+    /// is gone or already listed. Expired listings of the chat are closed first
+    /// so their items can be listed again.
+    pub async fn create_listing(
+        &self,
+        loot: &Loot,
+        seller_name: &str,
+        price: i32,
+        now: f64,
+        expires_at: f64,
+    ) -> Result<Option<MarketListing>, sqlx::Error> {
+        sqlx::query(
+            "UPDATE market_listings SET status = 'expired'
+             WHERE chat_id = $1 AND status = 'active' AND expires_at <= $2",
+        )
+        .bind(loot.chat_id)
+        .bind(now)
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query_as::<_, MarketListing>(
+            "INSERT INTO market_listings (chat_id, seller, seller_name, loot_uuid, item_name, item_icon,
+                                          price, created_at, expires_at)
+             SELECT chat_id, owner, $3, uuid, name, icon, $4, $5, $6
+             FROM loot WHERE uuid = $1 AND owner = $2
+             ON CONFLICT (loot_uuid) WHERE status = 'active' DO NOTHING
+             RETURNING id, chat_id, seller, seller_name, loot_uuid, item_name, item_icon, price,
+                       expires_at, buyer_name, fee, sold_at",
+        )
+        .bind(loot.uuid)
+        .bind(loot.owner)
+        .bind(seller_name)
+        .bind(price)
+        .bind(now)
+        .bind(expires_at)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Unexpired listings whose seller still owns the item, newest first.
+    pub async fn get_market_listings(
+        &self,
+        chat_id: i64,
+        now: f64,
+        limit: i64,
+        offset: i64,
+    ) -> Result<Vec<MarketListing>, sqlx::Error> {
+        sqlx::query_as::<_, MarketListing>(
+            "SELECT m.id, m.chat_id, m.seller, m.seller_name, m.loot_uuid, m.item_name, m.item_icon,
+                    m.price, m.expires_at, m.buyer_name, m.fee, m.sold_at
+             FROM market_listings m
+             JOIN loot l ON l.uuid = m.loot_uuid AND l.owner = m.seller
+             WHERE m.chat_id = $1 AND m.status = 'active' AND m.expires_at > $2
+             ORDER BY m.id DESC LIMIT $3 OFFSET $4",
+        )
+        .bind(chat_id)
+        .bind(now)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn count_market_listings(&self, chat_id: i64, now: f64) -> Result<i64, sqlx::Error> {
+        let (count,): (i64,) = sqlx::query_as(
+            "SELECT COUNT(*) FROM market_listings m
+             JOIN loot l ON l.uuid = m.loot_uuid AND l.owner = m.seller
+             WHERE m.chat_id = $1 AND m.status = 'active' AND m.expires_at > $2",
+        )
+        .bind(chat_id)
+        .bind(now)
+        .fetch_one(&self.pool)
+        .await?;
+        Ok(count)
+    }
+
+    /// Takes an active listing off the market if `seller` put it there.
+    pub async fn withdraw_listing(&self, listing_id: i32, seller: i64) -> Result<Option<MarketListing>, sqlx::Error> {
+        sqlx::query_as::<_, MarketListing>(
+            "UPDATE market_listings SET status = 'cancelled'
+             WHERE id = $1 AND seller = $2 AND status = 'active'
+             RETURNING id, chat_id, seller, seller_name, loot_uuid, item_name, item_icon, price,
+                       expires_at, buyer_name, fee, sold_at",
+        )
+        .bind(listing_id)
+        .bind(seller)
+        .fetch_optional(&self.pool)
+        .await
+    }
+
+    /// Buys a listing with salo from the buyer's farm. The price, minus the
+    /// `fee_share` the market burns, goes to the seller's farm, and the item
+    /// changes hands in the same transaction.
+    pub async fn buy_listing(
+        &self,
+        listing_id: i32,
+        buyer: i64,
+        buyer_name: &str,
+        buyer_max_items: i64,
+        fee_share: f64,
+        now: f64,
+    ) -> Result<Result<MarketListing, MarketFailure>, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+
+        let listing = sqlx::query_as::<_, MarketListing>(
+            "SELECT id, chat_id, seller, seller_name, loot_uuid, item_name, item_icon, price,
+                    expires_at, buyer_name, fee, sold_at
+             FROM market_listings
+             WHERE id = $1 AND status = 'active' AND expires_at > $2 AND seller <> $3
+             FOR UPDATE",
+        )
+        .bind(listing_id)
+        .bind(now)
+        .bind(buyer)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(listing) = listing else {
+            tx.rollback().await?;
+            return Ok(Err(MarketFailure::Closed));
+        };
+
+        let count = Self::count_loot_locked(&mut tx, listing.chat_id, buyer).await?;
+        if count >= buyer_max_items {
+            tx.rollback().await?;
+            return Ok(Err(MarketFailure::InventoryFull));
+        }
+
+        let paid = sqlx::query(
+            "UPDATE farms SET salo = salo - $3 WHERE chat_id = $1 AND user_id = $2 AND salo >= $3",
+        )
+        .bind(listing.chat_id)
+        .bind(buyer)
+        .bind(listing.price)
+        .execute(&mut *tx)
+        .await?;
+
+        if paid.rows_affected() == 0 {
+            tx.rollback().await?;
+            return Ok(Err(MarketFailure::NotEnoughSalo));
+        }
+
+        if !Self::transfer_loot(&mut tx, listing.chat_id, listing.loot_uuid, listing.seller, buyer).await? {
+            tx.rollback().await?;
+            self.withdraw_listing(listing.id, listing.seller).await?;
+            return Ok(Err(MarketFailure::ItemMissing));
+        }
+
+        let fee = (listing.price as f64 * fee_share).floor() as i32;
+        sqlx::query(
+            "UPDATE farms SET salo = salo + $3 WHERE chat_id = $1 AND user_id = $2",
+        )
+        .bind(listing.chat_id)
+        .bind(listing.seller)
+        .bind(listing.price - fee)
+        .execute(&mut *tx)
+        .await?;
+
+        let sold = sqlx::query_as::<_, MarketListing>(
+            "UPDATE market_listings
+             SET status = 'sold', buyer = $2, buyer_name = $3, fee = $4, sold_at = $5
+             WHERE id = $1
+             RETURNING id, chat_id, seller, seller_name, loot_uuid, item_name, item_icon, price,
+                       expires_at, buyer_name, fee, sold_at",
+        )
+        .bind(listing.id)
+        .bind(buyer)
+        .bind(buyer_name)
+        .bind(fee)
+        .bind(now)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Ok(sold))
+    }
+
+    /// The chat's latest market sales, newest first.
+    pub async fn get_market_sales(&self, chat_id: i64, limit: i64) -> Result<Vec<MarketListing>, sqlx::Error> {
+        sqlx::query_as::<_, MarketListing>(
+            "SELECT id, chat_id, seller, seller_name, loot_uuid, item_name, item_icon, price,
+                    expires_at, buyer_name, fee, sold_at
+             FROM market_listings WHERE chat_id = $1 AND status = 'sold'
+             ORDER BY sold_at DESC LIMIT $2",
+        )
+        .bind(chat_id)
+        .bind(limit)
         .fetch_all(&self.pool)
         .await
     }
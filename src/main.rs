@@ -6,7 +6,7 @@ mod database;
 mod modules;
 use config::Config;
 use database::Database;
-use modules::{ModuleManager, pig_game::PigGameModule, powerful_nahruk::PowerfulNahrukModule, reactions::ReactionsModule};
+use modules::{ModuleManager, pig_game::{PigGameModule, spawn_leader_job, spawn_season_job, spawn_world_event_job}, powerful_nahruk::PowerfulNahrukModule, reactions::ReactionsModule};
 
 #[tokio::main]
 async fn main() {
@@ -31,16 +31,27 @@ async fn main() {
     db.migrate().await.expect("Failed to run migrations");
     log::info!("Database migrations completed");
 
+    let config = Arc::new(config);
+
+    let token = std::env::var("TELEGRAM_TOKEN").expect("TELEGRAM_TOKEN not set");
+    let bot = Bot::new(token);
+    let me = bot.get_me().await.expect("Failed to fetch bot info");
+
     let mut module_manager = ModuleManager::new();
+    module_manager.set_bot_username(me.username());
     module_manager.register_module(Box::new(PigGameModule::new()));
     module_manager.register_module(Box::new(PowerfulNahrukModule::new()));
     module_manager.register_module(Box::new(ReactionsModule::new()));
+    log::info!("Modules in dispatch order: {:?}", module_manager.module_names());
     let module_manager = Arc::new(module_manager);
 
-    let config = Arc::new(config);
+    if let Err(e) = module_manager.publish_commands(&bot).await {
+        log::error!("Failed to publish bot commands: {}", e);
+    }
 
-    let token = std::env::var("TELEGRAM_TOKEN").expect("TELEGRAM_TOKEN not set");
-    let bot = Bot::new(token);
+    spawn_season_job(bot.clone(), db.clone(), config.clone());
+    spawn_world_event_job(bot.clone(), db.clone(), config.clone());
+    spawn_leader_job(bot.clone(), db.clone());
 
     let handler = dptree::entry()
         .branch(Update::filter_message().endpoint(handle_message))
@@ -61,38 +72,28 @@ async fn handle_message(
     db: Arc<Database>,
     config: Arc<Config>
 ) -> ResponseResult<()> {
-    if let Some(text) = msg.text() {
-        if text.starts_with('/') {
-            let parts: Vec<&str> = text[1..].split_whitespace().collect();
-            if let Some(command) = parts.first() {
-                let args = parts.get(1..).unwrap_or(&[]).to_vec();
-
-                match *command {
-                    "help" => {
-                        let commands = module_manager.get_all_commands();
-                        let help_text = if commands.is_empty() {
-                            "No commands available".to_string()
-                        } else {
-                            commands.join("\n")
-                        };
-                        bot.send_message(
-                            msg.chat.id,
-                            format!("Available commands:\n{}", help_text),
-                        )
-                        .await?;
-                        return Ok(());
-                    }
-                    _ => {
-                        if module_manager
-                            .handle_command(bot.clone(), msg.clone(), command, args, &db, &config)
-                            .await?
-                        {
-                            return Ok(());
-                        }
-                    }
-                }
-            }
-        }
+    if let Some(text) = msg.text()
+        && module_manager.command_name(text) == Some("help")
+    {
+        let commands = module_manager.get_all_commands();
+        let help_text = if commands.is_empty() {
+            "No commands available".to_string()
+        } else {
+            commands.join("\n")
+        };
+        bot.send_message(
+            msg.chat.id,
+            format!("Available commands:\n{}", help_text),
+        )
+        .await?;
+        return Ok(());
+    }
+
+    if module_manager
+        .handle_command(bot.clone(), msg.clone(), &db, &config)
+        .await?
+    {
+        return Ok(());
     }
 
     module_manager.handle_message(bot, msg, &db, &config).await?;
@@ -0,0 +1,137 @@
+use std::fmt;
+use std::str::FromStr;
+use teloxide::{
+    prelude::*,
+    types::{CallbackQuery, InlineKeyboardButton},
+};
+
+/// Telegram rejects buttons whose callback data is longer than this, in bytes.
+pub const MAX_CALLBACK_DATA_LEN: usize = 64;
+
+const SEPARATOR: char = ':';
+
+/// Answer for buttons no module claims or whose payload no longer makes sense.
+pub const STALE_CALLBACK_TEXT: &str = "Эта кнопка устарела, вызови команду заново";
+
+/// Callback data of an inline button: the namespace of the module that owns
+/// it followed by its fields, as `namespace:field:field`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackData {
+    namespace: String,
+    fields: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CallbackDataError {
+    /// A field contains the separator and would shift the fields after it.
+    Separator(String),
+    /// The encoded data is longer than Telegram allows.
+    TooLong(usize),
+}
+
+impl fmt::Display for CallbackDataError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallbackDataError::Separator(field) => write!(f, "callback field {:?} contains '{}'", field, SEPARATOR),
+            CallbackDataError::TooLong(len) => {
+                write!(f, "callback data is {} bytes, Telegram allows {}", len, MAX_CALLBACK_DATA_LEN)
+            }
+        }
+    }
+}
+
+impl CallbackData {
+    pub fn new(namespace: &str) -> Self {
+        Self { namespace: namespace.to_string(), fields: Vec::new() }
+    }
+
+    pub fn with(mut self, field: impl fmt::Display) -> Self {
+        self.fields.push(field.to_string());
+        self
+    }
+
+    pub fn encode(&self) -> Result<String, CallbackDataError> {
+        let mut data = self.namespace.clone();
+        for field in &self.fields {
+            if field.contains(SEPARATOR) {
+                return Err(CallbackDataError::Separator(field.clone()));
+            }
+            data.push(SEPARATOR);
+            data.push_str(field);
+        }
+
+        if data.len() > MAX_CALLBACK_DATA_LEN {
+            return Err(CallbackDataError::TooLong(data.len()));
+        }
+        Ok(data)
+    }
+
+    /// `None` for empty data.
+    pub fn decode(data: &str) -> Option<Self> {
+        let mut parts = data.split(SEPARATOR);
+        let namespace = parts.next().filter(|namespace| !namespace.is_empty())?;
+        Some(Self {
+            namespace: namespace.to_string(),
+            fields: parts.map(str::to_string).collect(),
+        })
+    }
+
+    pub fn namespace(&self) -> &str {
+        &self.namespace
+    }
+
+    pub fn field(&self, index: usize) -> Option<&str> {
+        self.fields.get(index).map(String::as_str)
+    }
+
+    /// Field `index` parsed as `T`; `None` if missing or malformed.
+    pub fn get<T: FromStr>(&self, index: usize) -> Option<T> {
+        self.field(index)?.parse().ok()
+    }
+
+    /// An inline button carrying this data. Data that cannot be encoded is
+    /// logged and replaced by the bare namespace, which answers as stale.
+    pub fn button(&self, text: impl Into<String>) -> InlineKeyboardButton {
+        let data = self.encode().unwrap_or_else(|e| {
+            log::error!("Invalid callback data for {}: {}", self.namespace, e);
+            self.namespace.clone()
+        });
+        InlineKeyboardButton::callback(text, data)
+    }
+}
+
+pub async fn answer_stale(bot: &Bot, query: &CallbackQuery) -> ResponseResult<()> {
+    bot.answer_callback_query(&query.id).text(STALE_CALLBACK_TEXT).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trips_typed_fields() {
+        let data = CallbackData::new("top").with(42_i64).with("weight").with(3_usize);
+        let encoded = data.encode().unwrap();
+        assert_eq!(encoded, "top:42:weight:3");
+
+        let decoded = CallbackData::decode(&encoded).unwrap();
+        assert_eq!(decoded, data);
+        assert_eq!(decoded.namespace(), "top");
+        assert_eq!(decoded.get::<i64>(0), Some(42));
+        assert_eq!(decoded.field(1), Some("weight"));
+        assert_eq!(decoded.get::<usize>(1), None);
+        assert_eq!(decoded.get::<usize>(3), None);
+    }
+
+    #[test]
+    fn rejects_data_telegram_would_refuse() {
+        let long = CallbackData::new("breed").with(i64::MAX).with(i64::MAX).with(i64::MAX).with("a");
+        assert_eq!(long.encode(), Err(CallbackDataError::TooLong(67)));
+        assert_eq!(
+            CallbackData::new("x").with("a:b").encode(),
+            Err(CallbackDataError::Separator("a:b".to_string()))
+        );
+        assert_eq!(CallbackData::decode(""), None);
+    }
+}
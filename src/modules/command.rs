@@ -0,0 +1,440 @@
+use std::fmt;
+use std::ops::Range;
+use teloxide::types::{BotCommand, BotCommandScope, Message, MessageEntityKind, UserId};
+use super::permissions::Role;
+
+/// Kind of value a command argument accepts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArgKind {
+    /// `@username` or a text mention of a user without a username.
+    User,
+    Integer,
+    /// A single word.
+    Word,
+    /// Everything left on the line.
+    Rest,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ArgSpec {
+    pub name: &'static str,
+    pub kind: ArgKind,
+    pub optional: bool,
+}
+
+/// Chats whose Telegram command menu lists a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommandScope {
+    /// Group and private chats.
+    All,
+    /// Group chats only, e.g. commands that need another player.
+    Groups,
+    /// Private chats only.
+    Private,
+    /// Group chat administrators only.
+    Admins,
+}
+
+impl CommandScope {
+    /// Menus the command menu is published for.
+    const MENUS: [CommandScope; 3] = [CommandScope::Groups, CommandScope::Private, CommandScope::Admins];
+
+    /// Whether a command of this scope belongs in `menu`. Administrators see
+    /// everything other group members see.
+    fn shown_in(self, menu: CommandScope) -> bool {
+        match menu {
+            CommandScope::Groups => matches!(self, CommandScope::All | CommandScope::Groups),
+            CommandScope::Private => matches!(self, CommandScope::All | CommandScope::Private),
+            CommandScope::Admins => self != CommandScope::Private,
+            CommandScope::All => self == CommandScope::All,
+        }
+    }
+
+    fn telegram_scope(self) -> BotCommandScope {
+        match self {
+            CommandScope::All => BotCommandScope::Default,
+            CommandScope::Groups => BotCommandScope::AllGroupChats,
+            CommandScope::Private => BotCommandScope::AllPrivateChats,
+            CommandScope::Admins => BotCommandScope::AllChatAdministrators,
+        }
+    }
+}
+
+/// A command a module answers to, with the arguments it takes.
+#[derive(Debug, Clone)]
+pub struct CommandSpec {
+    pub name: &'static str,
+    pub aliases: Vec<&'static str>,
+    /// Default description, shown to users without a translation.
+    pub description: &'static str,
+    /// Descriptions by IETF language code.
+    pub translations: Vec<(&'static str, &'static str)>,
+    pub scope: CommandScope,
+    /// Role the caller needs; checked before the arguments are parsed.
+    pub role: Role,
+    pub args: Vec<ArgSpec>,
+}
+
+impl CommandSpec {
+    pub fn new(name: &'static str, description: &'static str) -> Self {
+        Self {
+            name,
+            aliases: Vec::new(),
+            description,
+            translations: Vec::new(),
+            scope: CommandScope::All,
+            role: Role::Anyone,
+            args: Vec::new(),
+        }
+    }
+
+    /// Another name the command answers to. Aliases are not published to
+    /// Telegram's command menu.
+    pub fn alias(mut self, alias: &'static str) -> Self {
+        self.aliases.push(alias);
+        self
+    }
+
+    pub fn translate(mut self, language: &'static str, description: &'static str) -> Self {
+        self.translations.push((language, description));
+        self
+    }
+
+    pub fn scope(mut self, scope: CommandScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn role(mut self, role: Role) -> Self {
+        self.role = role;
+        self
+    }
+
+    pub fn description_for(&self, language: Option<&str>) -> &'static str {
+        language
+            .and_then(|language| self.translations.iter().find(|(code, _)| *code == language))
+            .map(|(_, description)| *description)
+            .unwrap_or(self.description)
+    }
+
+    pub fn arg(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, kind, optional: false });
+        self
+    }
+
+    pub fn optional(mut self, name: &'static str, kind: ArgKind) -> Self {
+        self.args.push(ArgSpec { name, kind, optional: true });
+        self
+    }
+
+    pub fn matches(&self, name: &str) -> bool {
+        self.name == name || self.aliases.contains(&name)
+    }
+
+    /// `/duel <ставка> [имя]`
+    pub fn usage(&self) -> String {
+        let mut usage = format!("/{}", self.name);
+        for arg in &self.args {
+            let name = if arg.kind == ArgKind::Rest { format!("{}...", arg.name) } else { arg.name.to_string() };
+            if arg.optional {
+                usage.push_str(&format!(" [{}]", name));
+            } else {
+                usage.push_str(&format!(" <{}>", name));
+            }
+        }
+        usage
+    }
+
+    /// Parses the text after the command. `mentions` are text mentions as
+    /// byte ranges into `text`.
+    pub fn parse(&self, text: &str, mentions: &[(Range<usize>, UserId)]) -> Result<Vec<Option<Arg>>, ArgError> {
+        let tokens = tokenize(text, mentions);
+        let mut tokens = tokens.iter().peekable();
+        let mut args = Vec::with_capacity(self.args.len());
+
+        for spec in &self.args {
+            let Some(token) = tokens.peek() else {
+                if !spec.optional {
+                    return Err(ArgError::Missing(spec.name));
+                }
+                args.push(None);
+                continue;
+            };
+
+            let value = match spec.kind {
+                ArgKind::Rest => {
+                    let rest = text[token.range.start..].trim_end().to_string();
+                    tokens.by_ref().for_each(drop);
+                    Arg::Text(rest)
+                }
+                ArgKind::Word => Arg::Text(text[token.range.clone()].to_string()),
+                ArgKind::Integer => {
+                    let raw = &text[token.range.clone()];
+                    raw.parse()
+                        .map(Arg::Integer)
+                        .map_err(|_| ArgError::Invalid(spec.name, raw.to_string()))?
+                }
+                ArgKind::User => {
+                    let raw = &text[token.range.clone()];
+                    match (token.mention, raw.strip_prefix('@')) {
+                        (Some(user_id), _) => Arg::User(UserArg::Id(user_id)),
+                        (None, Some(username)) if !username.is_empty() => Arg::User(UserArg::Username(username.to_string())),
+                        _ => return Err(ArgError::Invalid(spec.name, raw.to_string())),
+                    }
+                }
+            };
+            tokens.next();
+            args.push(Some(value));
+        }
+
+        match tokens.next() {
+            Some(extra) => Err(ArgError::Unexpected(text[extra.range.clone()].to_string())),
+            None => Ok(args),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UserArg {
+    Username(String),
+    Id(UserId),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Arg {
+    User(UserArg),
+    Integer(i64),
+    Text(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ArgError {
+    Missing(&'static str),
+    Invalid(&'static str, String),
+    Unexpected(String),
+}
+
+impl fmt::Display for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ArgError::Missing(name) => write!(f, "Не хватает аргумента <{}>", name),
+            ArgError::Invalid(name, value) => write!(f, "«{}» не подходит как <{}>", value, name),
+            ArgError::Unexpected(value) => write!(f, "Лишний аргумент «{}»", value),
+        }
+    }
+}
+
+/// A command with its arguments checked against its `CommandSpec`.
+#[derive(Debug, Clone)]
+pub struct ParsedCommand {
+    /// Canonical name, even when invoked through an alias.
+    pub name: &'static str,
+    args: Vec<Option<Arg>>,
+    raw: String,
+}
+
+impl ParsedCommand {
+    pub fn new(name: &'static str, args: Vec<Option<Arg>>, raw: &str) -> Self {
+        Self { name, args, raw: raw.to_string() }
+    }
+
+    pub fn integer(&self, index: usize) -> Option<i64> {
+        match self.args.get(index)? {
+            Some(Arg::Integer(value)) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn text(&self, index: usize) -> Option<&str> {
+        match self.args.get(index)? {
+            Some(Arg::Text(value)) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn user(&self, index: usize) -> Option<&UserArg> {
+        match self.args.get(index)? {
+            Some(Arg::User(user)) => Some(user),
+            _ => None,
+        }
+    }
+
+    /// The argument text split on whitespace, for handlers that read it as words.
+    pub fn words(&self) -> Vec<&str> {
+        self.raw.split_whitespace().collect()
+    }
+}
+
+/// Commands Telegram shows for one scope and language.
+#[derive(Debug, Clone)]
+pub struct CommandMenu {
+    pub scope: BotCommandScope,
+    /// `None` for users whose language has no translation.
+    pub language: Option<&'static str>,
+    pub commands: Vec<BotCommand>,
+}
+
+/// Builds a menu for every scope in every language some command is
+/// translated to. Untranslated commands fall back to their default description.
+pub fn command_menus(specs: &[CommandSpec]) -> Vec<CommandMenu> {
+    let mut languages: Vec<Option<&'static str>> = vec![None];
+    for (language, _) in specs.iter().flat_map(|spec| &spec.translations) {
+        if !languages.contains(&Some(*language)) {
+            languages.push(Some(*language));
+        }
+    }
+
+    let mut menus = Vec::new();
+    for menu in CommandScope::MENUS {
+        for &language in &languages {
+            let commands = specs
+                .iter()
+                .filter(|spec| spec.scope.shown_in(menu))
+                .map(|spec| BotCommand::new(spec.name, spec.description_for(language)))
+                .collect();
+            menus.push(CommandMenu { scope: menu.telegram_scope(), language, commands });
+        }
+    }
+    menus
+}
+
+struct Token {
+    range: Range<usize>,
+    mention: Option<UserId>,
+}
+
+/// Splits on whitespace, keeping each text mention a single token.
+fn tokenize(text: &str, mentions: &[(Range<usize>, UserId)]) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let Some(skip) = rest.find(|c: char| !c.is_whitespace()) else {
+            break;
+        };
+        pos += skip;
+
+        if let Some((range, user_id)) = mentions.iter().find(|(range, _)| range.start == pos) {
+            tokens.push(Token { range: range.clone(), mention: Some(*user_id) });
+            pos = range.end;
+            continue;
+        }
+
+        let len = text[pos..].find(char::is_whitespace).unwrap_or(text.len() - pos);
+        tokens.push(Token { range: pos..pos + len, mention: None });
+        pos += len;
+    }
+    tokens
+}
+
+/// Splits `/name@bot args` into the command name, its argument text and the
+/// argument text's byte offset. Returns `None` for non-commands and commands
+/// addressed to another bot.
+pub fn split_command<'a>(text: &'a str, bot_username: Option<&str>) -> Option<(&'a str, &'a str, usize)> {
+    let body = text.strip_prefix('/')?;
+    let head_len = body.find(char::is_whitespace).unwrap_or(body.len());
+    let head = &body[..head_len];
+
+    let name = match head.split_once('@') {
+        Some((name, target)) => {
+            if bot_username.is_some_and(|username| !target.eq_ignore_ascii_case(username)) {
+                return None;
+            }
+            name
+        }
+        None => head,
+    };
+    if name.is_empty() {
+        return None;
+    }
+
+    let rest = &body[head_len..];
+    let args = rest.trim_start();
+    let offset = 1 + head_len + (rest.len() - args.len());
+    Some((name, args, offset))
+}
+
+/// Text mentions of `msg` as byte ranges relative to `offset`.
+pub fn text_mentions(msg: &Message, offset: usize) -> Vec<(Range<usize>, UserId)> {
+    msg.parse_entities()
+        .unwrap_or_default()
+        .iter()
+        .filter(|entity| entity.start() >= offset)
+        .filter_map(|entity| match entity.kind() {
+            MessageEntityKind::TextMention { user } => Some((entity.start() - offset..entity.end() - offset, user.id)),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn duel() -> CommandSpec {
+        CommandSpec::new("duel", "").arg("ставка", ArgKind::Integer).optional("имя", ArgKind::Rest)
+    }
+
+    #[test]
+    fn strips_own_bot_suffix_and_ignores_other_bots() {
+        assert_eq!(split_command("/grow@PigBot", Some("pigbot")), Some(("grow", "", 12)));
+        assert_eq!(split_command("/grow@OtherBot", Some("pigbot")), None);
+        assert_eq!(split_command("/duel  5 Борька", None), Some(("duel", "5 Борька", 7)));
+        assert_eq!(split_command("grow", None), None);
+        assert_eq!(split_command("/ grow", None), None);
+    }
+
+    #[test]
+    fn parses_typed_arguments() {
+        let args = duel().parse("15 Хряк Петрович", &[]).unwrap();
+        assert_eq!(args, vec![Some(Arg::Integer(15)), Some(Arg::Text("Хряк Петрович".to_string()))]);
+
+        let args = duel().parse("15", &[]).unwrap();
+        assert_eq!(args, vec![Some(Arg::Integer(15)), None]);
+    }
+
+    #[test]
+    fn reports_usage_errors() {
+        assert_eq!(duel().parse("", &[]), Err(ArgError::Missing("ставка")));
+        assert_eq!(duel().parse("много", &[]), Err(ArgError::Invalid("ставка", "много".to_string())));
+        let spec = CommandSpec::new("sell", "").arg("номер", ArgKind::Integer).arg("цена", ArgKind::Integer);
+        assert_eq!(spec.parse("1 2 3", &[]), Err(ArgError::Unexpected("3".to_string())));
+        assert_eq!(duel().usage(), "/duel <ставка> [имя...]");
+    }
+
+    #[test]
+    fn menus_respect_scopes_and_fall_back_to_default_description() {
+        let specs = vec![
+            CommandSpec::new("my", "Моя свинья").translate("en", "My pig"),
+            CommandSpec::new("duel", "Дуэль").scope(CommandScope::Groups),
+            CommandSpec::new("enable", "Включить модуль").scope(CommandScope::Admins),
+        ];
+        let menus = command_menus(&specs);
+        assert_eq!(menus.len(), 6);
+
+        let names = |scope: BotCommandScope, language: Option<&str>| {
+            let menu = menus.iter().find(|m| m.scope == scope && m.language == language).unwrap();
+            menu.commands.iter().map(|c| (c.command.as_str(), c.description.as_str())).collect::<Vec<_>>()
+        };
+        assert_eq!(names(BotCommandScope::AllPrivateChats, None), vec![("my", "Моя свинья")]);
+        assert_eq!(names(BotCommandScope::AllGroupChats, Some("en")), vec![("my", "My pig"), ("duel", "Дуэль")]);
+        assert_eq!(
+            names(BotCommandScope::AllChatAdministrators, None),
+            vec![("my", "Моя свинья"), ("duel", "Дуэль"), ("enable", "Включить модуль")]
+        );
+    }
+
+    #[test]
+    fn parses_usernames_and_text_mentions() {
+        let spec = CommandSpec::new("grant", "").optional("участник", ArgKind::User);
+        assert_eq!(
+            spec.parse("@vasya", &[]).unwrap(),
+            vec![Some(Arg::User(UserArg::Username("vasya".to_string())))]
+        );
+        assert_eq!(
+            spec.parse("Вася Пупкин", &[(0.."Вася Пупкин".len(), UserId(42))]).unwrap(),
+            vec![Some(Arg::User(UserArg::Id(UserId(42))))]
+        );
+        assert_eq!(spec.parse("vasya", &[]), Err(ArgError::Invalid("участник", "vasya".to_string())));
+    }
+}
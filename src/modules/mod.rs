@@ -1,19 +1,50 @@
 use crate::database::Database;
 use async_trait::async_trait;
-use std::collections::HashMap;
-use teloxide::{prelude::*, types::Message};
+use teloxide::{prelude::*, sugar::request::RequestReplyExt, types::Message};
 use crate::config::Config;
+use callback::{CallbackData, answer_stale};
+use command::{CommandSpec, ParsedCommand, command_menus, split_command, text_mentions};
+use permissions::{Role, deny, has_role, role_commands};
+use settings::{MODULES_NAMESPACE, settings_commands};
+
+/// What happens to a message after a module handled it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dispatch {
+    /// Lower-priority modules do not see it.
+    Stop,
+    /// Lower-priority modules see it as well.
+    FanOut,
+}
+
+impl Dispatch {
+    fn passes_on(self, handled: bool) -> bool {
+        !handled || self == Dispatch::FanOut
+    }
+}
 
 #[async_trait]
 pub trait BotModule: Send + Sync {
     fn name(&self) -> &'static str;
-    fn commands(&self) -> Vec<(&'static str, &'static str)>;
+    /// Short name admins pass to /enable and /disable.
+    fn key(&self) -> &'static str;
+    /// Modules with a higher priority get commands and messages first.
+    /// Equal priorities keep their registration order.
+    fn priority(&self) -> i32 {
+        0
+    }
+    fn dispatch(&self) -> Dispatch {
+        Dispatch::Stop
+    }
+    fn commands(&self) -> Vec<CommandSpec>;
+    /// Custom roles the module checks beyond those its command specs require.
+    fn roles(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
     async fn handle_command(
         &self,
         bot: Bot,
         msg: Message,
-        command: &str,
-        args: Vec<&str>,
+        command: &ParsedCommand,
         db: &Database,
         config: &Config,
     ) -> ResponseResult<()>;
@@ -26,52 +57,138 @@ pub trait BotModule: Send + Sync {
     ) -> ResponseResult<bool> {
         Ok(false)
     }
+    /// First segments of the callback data of this module's buttons.
+    fn callback_namespaces(&self) -> Vec<&'static str> {
+        Vec::new()
+    }
     async fn handle_callback_query(
         &self,
-        _bot: Bot,
-        _query: CallbackQuery,
+        bot: Bot,
+        query: CallbackQuery,
+        _data: &CallbackData,
         _db: &Database,
         _config: &Config,
     ) -> ResponseResult<()> {
-        // Default implementation - do nothing
-        Ok(())
+        answer_stale(&bot, &query).await
     }
 }
 
 pub struct ModuleManager {
-    modules: HashMap<String, Box<dyn BotModule>>,
+    /// Sorted by descending priority.
+    modules: Vec<Box<dyn BotModule>>,
+    bot_username: Option<String>,
 }
 
 impl ModuleManager {
     pub fn new() -> Self {
         Self {
-            modules: HashMap::new(),
+            modules: Vec::new(),
+            bot_username: None,
         }
     }
 
+    /// Commands addressed as `/command@other_bot` are ignored once this is set.
+    pub fn set_bot_username(&mut self, username: &str) {
+        self.bot_username = Some(username.to_string());
+    }
+
+    /// Name of the command in `text`, if it is addressed to this bot.
+    pub fn command_name<'a>(&self, text: &'a str) -> Option<&'a str> {
+        split_command(text, self.bot_username.as_deref()).map(|(name, _, _)| name)
+    }
+
     pub fn register_module(&mut self, module: Box<dyn BotModule>) {
-        let name = module.name().to_string();
-        self.modules.insert(name, module);
+        for namespace in module.callback_namespaces() {
+            if let Some(owner) = self.callback_owner(namespace) {
+                log::warn!(
+                    "Callback namespace {} of {} is already claimed by {}",
+                    namespace,
+                    module.name(),
+                    owner.name()
+                );
+            }
+        }
+        let position = self.modules.partition_point(|m| m.priority() >= module.priority());
+        self.modules.insert(position, module);
+    }
+
+    /// Module names in the order they get messages.
+    pub fn module_names(&self) -> Vec<&'static str> {
+        self.modules.iter().map(|m| m.name()).collect()
+    }
+
+    /// Commands the manager answers itself rather than a module.
+    fn manager_commands() -> Vec<CommandSpec> {
+        let mut specs = settings_commands();
+        specs.extend(role_commands());
+        specs
     }
 
+    /// Parses a command message against the spec of the module that owns it
+    /// and dispatches it. Callers without the role the spec requires get the
+    /// uniform denial, malformed arguments a usage reply.
+    /// Returns `false` if no module enabled in the chat has the command.
     pub async fn handle_command(
         &self,
         bot: Bot,
         msg: Message,
-        command: &str,
-        args: Vec<&str>,
         db: &Database,
         config: &Config
     ) -> ResponseResult<bool> {
-        for module in self.modules.values() {
-            if module.commands().iter().any(|(cmd, _)| *cmd == command) {
-                module
-                    .handle_command(bot.clone(), msg.clone(), command, args.clone(), db, config)
+        let Some(text) = msg.text() else {
+            return Ok(false);
+        };
+        let Some((name, args, offset)) = split_command(text, self.bot_username.as_deref()) else {
+            return Ok(false);
+        };
+
+        let (spec, module) = match Self::manager_commands().into_iter().find(|spec| spec.matches(name)) {
+            Some(spec) => (spec, None),
+            None => {
+                let disabled = self.disabled_modules(msg.chat.id, db).await;
+                let found = self
+                    .modules
+                    .iter()
+                    .filter(|module| !disabled.iter().any(|key| key == module.key()))
+                    .find_map(|module| {
+                        let spec = module.commands().into_iter().find(|spec| spec.matches(name))?;
+                        Some((spec, Some(module)))
+                    });
+                match found {
+                    Some(found) => found,
+                    None => return Ok(false),
+                }
+            }
+        };
+
+        if spec.role != Role::Anyone {
+            let Some(user) = msg.from.as_ref() else {
+                return Ok(true);
+            };
+            if !has_role(&bot, db, config, msg.chat.id, user.id, spec.role).await {
+                deny(&bot, &msg, spec.role).await?;
+                return Ok(true);
+            }
+        }
+
+        let command = match spec.parse(args, &text_mentions(&msg, offset)) {
+            Ok(parsed) => ParsedCommand::new(spec.name, parsed, args),
+            Err(e) => {
+                bot.send_message(msg.chat.id, format!("{}\nИспользование: {}", e, spec.usage()))
+                    .reply_to(msg.id)
                     .await?;
                 return Ok(true);
             }
+        };
+
+        match module {
+            Some(module) => module.handle_command(bot, msg, &command, db, config).await?,
+            None if role_commands().iter().any(|spec| spec.name == command.name) => {
+                self.handle_role_command(bot, msg, &command, db).await?
+            }
+            None => self.handle_settings_command(bot, msg, &command, db).await?,
         }
-        Ok(false)
+        Ok(true)
     }
 
     pub async fn handle_message(
@@ -81,14 +198,29 @@ impl ModuleManager {
         db: &Database,
         config: &Config
     ) -> ResponseResult<()> {
-        for module in self.modules.values() {
-            if module.handle_message(bot.clone(), msg.clone(), db, config).await? {
-                break; // Stop at first module that handles the message
+        let disabled = self.disabled_modules(msg.chat.id, db).await;
+        for module in &self.modules {
+            if disabled.iter().any(|key| key == module.key()) {
+                continue;
+            }
+            let handled = module.handle_message(bot.clone(), msg.clone(), db, config).await?;
+            if !module.dispatch().passes_on(handled) {
+                break;
             }
         }
         Ok(())
     }
 
+    /// The module whose buttons use `namespace`. The first claim wins.
+    fn callback_owner(&self, namespace: &str) -> Option<&dyn BotModule> {
+        self.modules
+            .iter()
+            .find(|m| m.callback_namespaces().contains(&namespace))
+            .map(|m| m.as_ref())
+    }
+
+    /// Routes a button press to the module that claims its namespace.
+    /// Presses no module claims get the stale button answer.
     pub async fn handle_callback_query(
         &self,
         bot: Bot,
@@ -96,18 +228,53 @@ impl ModuleManager {
         db: &Database,
         config: &Config
     ) -> ResponseResult<()> {
-        for module in self.modules.values() {
-            module.handle_callback_query(bot.clone(), query.clone(), db, config).await?;
+        let Some(data) = query.data.as_deref().and_then(CallbackData::decode) else {
+            return answer_stale(&bot, &query).await;
+        };
+        if data.namespace() == MODULES_NAMESPACE {
+            return self.handle_settings_callback(&bot, &query, &data, db, config).await;
+        }
+        let Some(module) = self.callback_owner(data.namespace()) else {
+            return answer_stale(&bot, &query).await;
+        };
+
+        if let Some(message) = &query.message
+            && self.disabled_modules(message.chat().id, db).await.iter().any(|key| key == module.key())
+        {
+            bot.answer_callback_query(&query.id)
+                .text("Этот модуль выключен в чате")
+                .await?;
+            return Ok(());
+        }
+        module.handle_callback_query(bot, query, &data, db, config).await
+    }
+
+    /// Publishes the commands of all registered modules, plus /help, as
+    /// Telegram's command menus. Superuser commands stay unlisted.
+    pub async fn publish_commands(&self, bot: &Bot) -> ResponseResult<()> {
+        let mut specs = vec![CommandSpec::new("help", "Список команд").translate("en", "List commands")];
+        specs.extend(Self::manager_commands());
+        for module in &self.modules {
+            specs.extend(module.commands());
+        }
+        specs.retain(|spec| spec.role != Role::Superuser);
+
+        for menu in command_menus(&specs) {
+            let request = bot.set_my_commands(menu.commands).scope(menu.scope);
+            match menu.language {
+                Some(language) => request.language_code(language).await?,
+                None => request.await?,
+            };
         }
         Ok(())
     }
 
     pub fn get_all_commands(&self) -> Vec<String> {
         let mut result = Vec::new();
-        for module in self.modules.values() {
+        for module in &self.modules {
             result.push(format!("{}:", module.name()));
-            for (cmd, desc) in module.commands() {
-                result.push(format!("/{} - {}", cmd, desc));
+            for spec in module.commands() {
+                result.push(format!("{} - {}", spec.usage(), spec.description));
             }
             result.push(String::new())
         }
@@ -115,6 +282,75 @@ impl ModuleManager {
     }
 }
 
+pub mod callback;
+pub mod command;
+pub mod permissions;
+pub mod settings;
 pub mod pig_game;
 pub mod powerful_nahruk;
 pub mod reactions;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pig_game::PigGameModule;
+    use powerful_nahruk::PowerfulNahrukModule;
+    use reactions::ReactionsModule;
+
+    struct Named(&'static str, &'static [&'static str]);
+
+    #[async_trait]
+    impl BotModule for Named {
+        fn name(&self) -> &'static str {
+            self.0
+        }
+        fn key(&self) -> &'static str {
+            self.0
+        }
+        fn callback_namespaces(&self) -> Vec<&'static str> {
+            self.1.to_vec()
+        }
+        fn commands(&self) -> Vec<CommandSpec> {
+            vec![]
+        }
+        async fn handle_command(&self, _: Bot, _: Message, _: &ParsedCommand, _: &Database, _: &Config) -> ResponseResult<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn modules_are_dispatched_by_priority_then_registration() {
+        let mut manager = ModuleManager::new();
+        manager.register_module(Box::new(ReactionsModule::new()));
+        manager.register_module(Box::new(Named("first", &[])));
+        manager.register_module(Box::new(PigGameModule::new()));
+        manager.register_module(Box::new(PowerfulNahrukModule::new()));
+        manager.register_module(Box::new(Named("second", &[])));
+
+        assert_eq!(
+            manager.module_names(),
+            vec!["Powerfull Nahruk", "first", "Pig Game", "second", "Reactions Module"]
+        );
+    }
+
+    #[test]
+    fn callbacks_go_to_the_first_module_claiming_their_namespace() {
+        let mut manager = ModuleManager::new();
+        manager.register_module(Box::new(Named("buttons", &["vote", "poll"])));
+        manager.register_module(Box::new(Named("late", &["poll"])));
+        manager.register_module(Box::new(PigGameModule::new()));
+
+        let owner = |namespace| manager.callback_owner(namespace).map(|m| m.name());
+        assert_eq!(owner("vote"), Some("buttons"));
+        assert_eq!(owner("poll"), Some("buttons"));
+        assert_eq!(owner("grow"), Some("Pig Game"));
+        assert_eq!(owner("unknown"), None);
+    }
+
+    #[test]
+    fn only_stopping_modules_swallow_handled_messages() {
+        assert!(!Dispatch::Stop.passes_on(true));
+        assert!(Dispatch::Stop.passes_on(false));
+        assert!(Dispatch::FanOut.passes_on(true));
+    }
+}
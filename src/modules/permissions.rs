@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, ChatId, Message, UserId},
+};
+use crate::config::Config;
+use crate::database::Database;
+use super::ModuleManager;
+use super::command::{ArgKind, CommandScope, CommandSpec, ParsedCommand, UserArg};
+
+/// Who may run a command. Superusers pass every check, and chat admins hold
+/// every custom role of their chat.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Anyone,
+    /// Granted to members by chat admins with /grant.
+    Custom(&'static str),
+    /// Telegram chat administrators; the user themselves in a private chat.
+    ChatAdmin,
+    /// Bot owners listed under `permissions.SUPERUSERS` in the config.
+    Superuser,
+}
+
+impl Role {
+    fn holders(self) -> String {
+        match self {
+            Role::Anyone => "все".to_string(),
+            Role::Custom(name) => format!("админы и игроки с ролью «{}»", name),
+            Role::ChatAdmin => "админы чата".to_string(),
+            Role::Superuser => "владельцы бота".to_string(),
+        }
+    }
+}
+
+/// The one answer to every call a role check turned down.
+pub fn denied_text(role: Role) -> String {
+    format!("🚫 Недостаточно прав: это могут только {}", role.holders())
+}
+
+pub async fn deny(bot: &Bot, msg: &Message, role: Role) -> ResponseResult<()> {
+    bot.send_message(msg.chat.id, denied_text(role)).reply_to(msg.id).await?;
+    Ok(())
+}
+
+pub async fn deny_callback(bot: &Bot, query: &CallbackQuery, role: Role) -> ResponseResult<()> {
+    bot.answer_callback_query(&query.id).text(denied_text(role)).await?;
+    Ok(())
+}
+
+/// Admin status of chat members as Telegram last reported it.
+#[derive(Default)]
+struct AdminCache {
+    entries: HashMap<(ChatId, UserId), (bool, Instant)>,
+}
+
+impl AdminCache {
+    fn get(&self, key: (ChatId, UserId), now: Instant, ttl: Duration) -> Option<bool> {
+        self.entries
+            .get(&key)
+            .filter(|(_, checked_at)| now.duration_since(*checked_at) < ttl)
+            .map(|(admin, _)| *admin)
+    }
+
+    /// Expired entries are dropped on the way so the cache only holds
+    /// recently active members.
+    fn insert(&mut self, key: (ChatId, UserId), admin: bool, now: Instant, ttl: Duration) {
+        self.entries.retain(|_, (_, checked_at)| now.duration_since(*checked_at) < ttl);
+        self.entries.insert(key, (admin, now));
+    }
+}
+
+static ADMIN_CACHE: LazyLock<Mutex<AdminCache>> = LazyLock::new(Default::default);
+
+/// Private chats have no admins, so their only member counts as one. Lookups
+/// are cached for `permissions.ROLE_CACHE_TTL`; failed lookups are not.
+pub async fn is_chat_admin(bot: &Bot, config: &Config, chat_id: ChatId, user_id: UserId) -> bool {
+    if chat_id.is_user() {
+        return true;
+    }
+
+    let ttl = Duration::from_secs(config.permissions.role_cache_ttl);
+    let key = (chat_id, user_id);
+    if let Some(admin) = ADMIN_CACHE.lock().unwrap().get(key, Instant::now(), ttl) {
+        return admin;
+    }
+
+    match bot.get_chat_member(chat_id, user_id).await {
+        Ok(member) => {
+            let admin = member.is_privileged();
+            ADMIN_CACHE.lock().unwrap().insert(key, admin, Instant::now(), ttl);
+            admin
+        }
+        Err(e) => {
+            log::error!("Failed to get chat member: {}", e);
+            false
+        }
+    }
+}
+
+/// Whether the user holds `role` in the chat. Telegram and the database are
+/// only asked when the cheaper checks did not decide.
+pub async fn has_role(bot: &Bot, db: &Database, config: &Config, chat_id: ChatId, user_id: UserId, role: Role) -> bool {
+    if role == Role::Anyone || config.permissions.is_superuser(user_id.0) {
+        return true;
+    }
+    if role == Role::Superuser {
+        return false;
+    }
+    if is_chat_admin(bot, config, chat_id, user_id).await {
+        return true;
+    }
+
+    let Role::Custom(name) = role else {
+        return false;
+    };
+    match db.has_chat_role(chat_id.0, user_id.0 as i64, name).await {
+        Ok(held) => held,
+        Err(e) => {
+            log::error!("Database error: {}", e);
+            false
+        }
+    }
+}
+
+/// Commands the manager answers itself to hand out custom roles.
+pub fn role_commands() -> Vec<CommandSpec> {
+    vec![
+        CommandSpec::new("roles", "Роли игроков в этом чате")
+            .translate("en", "Member roles in this chat")
+            .scope(CommandScope::Groups),
+        CommandSpec::new("grant", "Выдать роль игроку")
+            .translate("en", "Grant a role to a member")
+            .scope(CommandScope::Admins)
+            .role(Role::ChatAdmin)
+            .arg("роль", ArgKind::Word)
+            .optional("игрок", ArgKind::User),
+        CommandSpec::new("revoke", "Забрать роль у игрока")
+            .translate("en", "Revoke a member's role")
+            .scope(CommandScope::Admins)
+            .role(Role::ChatAdmin)
+            .arg("роль", ArgKind::Word)
+            .optional("игрок", ArgKind::User),
+    ]
+}
+
+impl ModuleManager {
+    /// Custom roles the registered modules check, which admins can /grant.
+    fn custom_roles(&self) -> Vec<&'static str> {
+        let mut roles = Vec::new();
+        for module in &self.modules {
+            let declared = module.commands().into_iter().filter_map(|spec| match spec.role {
+                Role::Custom(name) => Some(name),
+                _ => None,
+            });
+            for role in module.roles().into_iter().chain(declared) {
+                if !roles.contains(&role) {
+                    roles.push(role);
+                }
+            }
+        }
+        roles
+    }
+
+    /// `/roles`, and `/grant` or `/revoke <role> [member]`. The member comes
+    /// from a text mention or a reply; usernames cannot be resolved to users.
+    pub(super) async fn handle_role_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+
+        if command.name == "roles" {
+            let text = match db.get_chat_roles(chat_id).await {
+                Ok(roles) if roles.is_empty() => "🎖 В этом чате никому не выданы роли".to_string(),
+                Ok(roles) => {
+                    let mut lines = vec!["🎖 Роли в этом чате:".to_string()];
+                    lines.extend(roles.iter().map(|grant| format!("{} — {}", grant.role, grant.user_name)));
+                    lines.join("\n")
+                }
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных".to_string()
+                }
+            };
+            bot.send_message(msg.chat.id, text).await?;
+            return Ok(());
+        }
+
+        let name = command.text(0).unwrap_or_default();
+        let roles = self.custom_roles();
+        let Some(role) = roles.iter().find(|role| role.eq_ignore_ascii_case(name)) else {
+            let known = if roles.is_empty() { "ни одной".to_string() } else { roles.join(", ") };
+            bot.send_message(msg.chat.id, format!("Нет роли «{}». Есть: {}", name, known))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let replied = msg.reply_to_message().and_then(|reply| reply.from.as_ref());
+        let user_id = match command.user(1) {
+            Some(UserArg::Id(id)) => *id,
+            Some(UserArg::Username(username)) => {
+                bot.send_message(msg.chat.id, format!("Не знаю, кто такой @{}. Ответь командой на его сообщение", username))
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            None => match replied {
+                Some(user) => user.id,
+                None => {
+                    bot.send_message(msg.chat.id, format!("Назови игрока или ответь на его сообщение: /{} {} @игрок", command.name, role))
+                        .reply_to(msg.id)
+                        .await?;
+                    return Ok(());
+                }
+            },
+        };
+
+        let member = match bot.get_chat_member(msg.chat.id, user_id).await {
+            Ok(member) if member.is_present() => member,
+            Ok(_) | Err(_) => {
+                bot.send_message(msg.chat.id, "Этого игрока нет в чате").reply_to(msg.id).await?;
+                return Ok(());
+            }
+        };
+        let user_name = member.user.full_name();
+
+        let text = if command.name == "grant" {
+            match db.grant_chat_role(chat_id, user_id.0 as i64, &user_name, role).await {
+                Ok(true) => format!("🎖 {} теперь {}", user_name, role),
+                Ok(false) => format!("У {} уже есть роль {}", user_name, role),
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных".to_string()
+                }
+            }
+        } else {
+            match db.revoke_chat_role(chat_id, user_id.0 as i64, role).await {
+                Ok(true) => format!("{} больше не {}", user_name, role),
+                Ok(false) => format!("У {} нет роли {}", user_name, role),
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных".to_string()
+                }
+            }
+        };
+        bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn admin_status_expires_after_the_ttl() {
+        let ttl = Duration::from_secs(300);
+        let start = Instant::now();
+        let member = (ChatId(-100), UserId(1));
+        let other = (ChatId(-100), UserId(2));
+
+        let mut cache = AdminCache::default();
+        assert_eq!(cache.get(member, start, ttl), None);
+
+        cache.insert(member, true, start, ttl);
+        assert_eq!(cache.get(member, start + Duration::from_secs(299), ttl), Some(true));
+        assert_eq!(cache.get(member, start + ttl, ttl), None);
+
+        cache.insert(other, false, start + ttl, ttl);
+        assert_eq!(cache.get(other, start + ttl, ttl), Some(false));
+        assert_eq!(cache.entries.len(), 1);
+    }
+}
@@ -0,0 +1,289 @@
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::database::{Database, Pig};
+use super::buildings::Building;
+use super::helpers::unix_now;
+
+const DAY: i64 = 24 * 3600;
+/// How often the background job re-ranks chats for time spent in first place.
+const LEADER_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AchievementGoal {
+    Weight(i64),
+    Feeds(i64),
+    SingleLoss(i64),
+    DuelWins(i64),
+    BuildingLevels(i64),
+    /// Seconds spent in first place without interruption.
+    TopHold(i64),
+}
+
+pub struct Achievement {
+    pub key: &'static str,
+    pub icon: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub goal: AchievementGoal,
+}
+
+pub const ACHIEVEMENTS: &[Achievement] = &[
+    Achievement { key: "first_feed", icon: "🍽", name: "Первая жрачка", description: "Покормить свинью", goal: AchievementGoal::Feeds(1) },
+    Achievement { key: "glutton", icon: "🐷", name: "Обжора", description: "Покормить свинью 100 раз", goal: AchievementGoal::Feeds(100) },
+    Achievement { key: "weight_100", icon: "💯", name: "Сотка", description: "Откормить 100 кг", goal: AchievementGoal::Weight(100) },
+    Achievement { key: "weight_1000", icon: "🐘", name: "Тонна сала", description: "Откормить 1000 кг", goal: AchievementGoal::Weight(1000) },
+    Achievement { key: "loss_50", icon: "📉", name: "Жиросжигатель", description: "Потерять 50 кг за одну кормёжку", goal: AchievementGoal::SingleLoss(50) },
+    Achievement { key: "duelist", icon: "⚔️", name: "Дуэлянт", description: "Выиграть 10 дуэлей", goal: AchievementGoal::DuelWins(10) },
+    Achievement { key: "builder", icon: "🏗", name: "Прораб", description: "Набрать 10 уровней построек", goal: AchievementGoal::BuildingLevels(10) },
+    Achievement { key: "king", icon: "👑", name: "Король свинарника", description: "Продержаться первым в топе неделю", goal: AchievementGoal::TopHold(7 * DAY) },
+];
+
+/// Everything achievement goals are measured against.
+#[derive(Debug, Clone, Default)]
+pub struct AchievementProgress {
+    pub weight: i64,
+    pub feeds: i64,
+    pub max_loss: i64,
+    pub duel_wins: i64,
+    pub building_levels: i64,
+    pub top_hold: i64,
+}
+
+impl AchievementGoal {
+    pub fn target(self) -> i64 {
+        match self {
+            AchievementGoal::Weight(n)
+            | AchievementGoal::Feeds(n)
+            | AchievementGoal::SingleLoss(n)
+            | AchievementGoal::DuelWins(n)
+            | AchievementGoal::BuildingLevels(n)
+            | AchievementGoal::TopHold(n) => n,
+        }
+    }
+
+    pub fn current(self, progress: &AchievementProgress) -> i64 {
+        match self {
+            AchievementGoal::Weight(_) => progress.weight,
+            AchievementGoal::Feeds(_) => progress.feeds,
+            AchievementGoal::SingleLoss(_) => progress.max_loss,
+            AchievementGoal::DuelWins(_) => progress.duel_wins,
+            AchievementGoal::BuildingLevels(_) => progress.building_levels,
+            AchievementGoal::TopHold(_) => progress.top_hold,
+        }
+    }
+
+    pub fn is_reached(self, progress: &AchievementProgress) -> bool {
+        self.current(progress) >= self.target()
+    }
+
+    fn format_progress(self, progress: &AchievementProgress) -> String {
+        let current = self.current(progress).clamp(0, self.target());
+        match self {
+            AchievementGoal::TopHold(target) => format!("{}/{} дн.", current / DAY, target / DAY),
+            _ => format!("{}/{}", current, self.target()),
+        }
+    }
+}
+
+/// Spawns the job that keeps every chat's leader current, so first place is
+/// held for as long as it lasts even when nobody in the chat plays.
+pub fn spawn_leader_job(bot: Bot, db: Arc<Database>) {
+    let Some(min_hold) = ACHIEVEMENTS
+        .iter()
+        .filter_map(|achievement| match achievement.goal {
+            AchievementGoal::TopHold(secs) => Some(secs),
+            _ => None,
+        })
+        .min()
+    else {
+        return;
+    };
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(LEADER_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            let now = unix_now();
+            match db.update_chat_leaders(now, now - min_hold as f64, super::MODULE_KEY).await {
+                Ok(leaders) => {
+                    for (chat_id, user_id) in leaders {
+                        super::PigGameModule.announce_achievements(&bot, ChatId(chat_id), user_id, &db).await;
+                    }
+                }
+                Err(e) => log::error!("Leader job failed: {}", e),
+            }
+        }
+    });
+}
+
+impl super::PigGameModule {
+    async fn achievement_progress(&self, pig: &Pig, db: &Database) -> Result<AchievementProgress, sqlx::Error> {
+        let now = unix_now();
+        let counters = db.get_achievement_counters(pig.chat_id, pig.user_id).await?;
+        let top_hold = match db.update_chat_leader(pig.chat_id, now).await? {
+            Some((leader, since)) if leader == pig.user_id => (now - since) as i64,
+            _ => 0,
+        };
+
+        Ok(AchievementProgress {
+            weight: pig.weight as i64,
+            feeds: counters.feeds,
+            max_loss: counters.max_loss,
+            duel_wins: counters.duel_wins,
+            building_levels: Building::ALL.iter().map(|b| b.level(pig) as i64).sum(),
+            top_hold,
+        })
+    }
+
+    /// Unlocks every achievement the pig has reached and returns the new ones.
+    pub async fn check_achievements(&self, chat_id: i64, user_id: i64, db: &Database) -> Result<Vec<&'static Achievement>, sqlx::Error> {
+        let Some(pig) = db.get_pig(chat_id, user_id).await? else {
+            return Ok(Vec::new());
+        };
+        let progress = self.achievement_progress(&pig, db).await?;
+        let unlocked = db.get_unlocked_achievements(chat_id, user_id).await?;
+
+        let now = unix_now();
+        let mut fresh = Vec::new();
+        for achievement in ACHIEVEMENTS {
+            if unlocked.iter().any(|key| key == achievement.key) || !achievement.goal.is_reached(&progress) {
+                continue;
+            }
+            if db.unlock_achievement(chat_id, user_id, achievement.key, now).await? {
+                fresh.push(achievement);
+            }
+        }
+        Ok(fresh)
+    }
+
+    /// Runs after a game event and congratulates the player on anything new.
+    /// Failures are logged only: achievements must never break the event itself.
+    pub async fn announce_achievements(&self, bot: &Bot, chat_id: ChatId, user_id: i64, db: &Database) {
+        let fresh = match self.check_achievements(chat_id.0, user_id, db).await {
+            Ok(fresh) => fresh,
+            Err(e) => {
+                log::error!("Failed to check achievements: {}", e);
+                return;
+            }
+        };
+        if fresh.is_empty() {
+            return;
+        }
+
+        let owner = match db.get_pig(chat_id.0, user_id).await {
+            Ok(Some(pig)) => pig.owner_name,
+            _ => "???".to_string(),
+        };
+        let mut lines = vec![format!("🏅 {} получает достижение!", owner)];
+        for achievement in fresh {
+            lines.push(format!("{} {} — {}", achievement.icon, achievement.name, achievement.description));
+        }
+        if let Err(e) = bot.send_message(chat_id, lines.join("\n")).await {
+            log::error!("Failed to announce achievements: {}", e);
+        }
+    }
+
+    /// `/achievements [name]`: unlocked and locked achievements with progress.
+    pub async fn handle_achievements_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let pig = if args.is_empty() && msg.reply_to_message().is_none() {
+            db.get_pig(chat_id, user_id).await
+        } else {
+            self.resolve_target_pig(&msg, &args.join(" "), db).await
+        };
+
+        let loaded = match pig {
+            Ok(Some(pig)) => async {
+                // Catch up on anything reached outside of tracked events, e.g. time in first place.
+                self.check_achievements(chat_id, pig.user_id, db).await?;
+                let progress = self.achievement_progress(&pig, db).await?;
+                let unlocked = db.get_unlocked_achievements(chat_id, pig.user_id).await?;
+                Ok(Some((pig, progress, unlocked)))
+            }
+            .await,
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        let (pig, progress, unlocked) = match loaded {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Свинья не найдена").reply_to(msg.id).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let (done, locked): (Vec<&Achievement>, Vec<&Achievement>) = ACHIEVEMENTS
+            .iter()
+            .partition(|achievement| unlocked.iter().any(|key| key == achievement.key));
+
+        let mut lines = vec![format!("🏅 Достижения {} ({}/{})", pig.name, done.len(), ACHIEVEMENTS.len())];
+        if !done.is_empty() {
+            lines.push(String::new());
+            for achievement in done {
+                lines.push(format!("✅ {} {} — {}", achievement.icon, achievement.name, achievement.description));
+            }
+        }
+        if !locked.is_empty() {
+            lines.push(String::new());
+            for achievement in locked {
+                lines.push(format!(
+                    "🔒 {} {} — {} ({})",
+                    achievement.icon,
+                    achievement.name,
+                    achievement.description,
+                    achievement.goal.format_progress(&progress)
+                ));
+            }
+        }
+
+        bot.send_message(msg.chat.id, lines.join("\n")).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn achievement_keys_are_unique() {
+        for (i, achievement) in ACHIEVEMENTS.iter().enumerate() {
+            assert!(ACHIEVEMENTS[i + 1..].iter().all(|other| other.key != achievement.key), "{}", achievement.key);
+        }
+    }
+
+    #[test]
+    fn goals_compare_against_their_own_counter() {
+        let progress = AchievementProgress { weight: 150, max_loss: 20, top_hold: 8 * DAY, ..Default::default() };
+        assert!(AchievementGoal::Weight(100).is_reached(&progress));
+        assert!(!AchievementGoal::SingleLoss(50).is_reached(&progress));
+        assert!(AchievementGoal::TopHold(7 * DAY).is_reached(&progress));
+        assert!(!AchievementGoal::Feeds(1).is_reached(&progress));
+    }
+
+    #[test]
+    fn progress_is_capped_at_target() {
+        let progress = AchievementProgress { weight: 1500, top_hold: 3 * DAY, ..Default::default() };
+        assert_eq!(AchievementGoal::Weight(1000).format_progress(&progress), "1000/1000");
+        assert_eq!(AchievementGoal::TopHold(7 * DAY).format_progress(&progress), "3/7 дн.");
+    }
+}
@@ -0,0 +1,373 @@
+use rand::prelude::*;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{BreedFailure, BreedTerms, Database, PigTraits, RaiseFailure};
+use crate::modules::callback::{CallbackData, answer_stale};
+use crate::modules::command::ParsedCommand;
+use super::helpers::{format_duration, unix_now};
+
+/// Piglets a player's stable holds.
+const MAX_PIGLETS: i64 = 5;
+/// Chance for each trait of a piglet to mutate.
+const MUTATION_CHANCE: f64 = 0.3;
+/// Largest change a single mutation makes.
+const MUTATION_SPREAD: f64 = 0.05;
+const GROWTH_BIAS_RANGE: (f64, f64) = (-0.1, 0.3);
+const LOSS_RESISTANCE_RANGE: (f64, f64) = (0.0, 0.3);
+const DUEL_STRENGTH_RANGE: (f64, f64) = (-0.1, 0.3);
+
+/// Each trait comes from one parent or their average, then may mutate.
+pub fn mix_traits(a: &PigTraits, b: &PigTraits, rng: &mut impl Rng) -> PigTraits {
+    let mut inherit = |x: f64, y: f64, (min, max): (f64, f64)| {
+        let base = match rng.random_range(0..3) {
+            0 => x,
+            1 => y,
+            _ => (x + y) / 2.0,
+        };
+        let mutation = if rng.random_bool(MUTATION_CHANCE) {
+            rng.random_range(-MUTATION_SPREAD..=MUTATION_SPREAD)
+        } else {
+            0.0
+        };
+        ((base + mutation).clamp(min, max) * 1000.0).round() / 1000.0
+    };
+
+    PigTraits {
+        growth_bias: inherit(a.growth_bias, b.growth_bias, GROWTH_BIAS_RANGE),
+        loss_resistance: inherit(a.loss_resistance, b.loss_resistance, LOSS_RESISTANCE_RANGE),
+        duel_strength: inherit(a.duel_strength, b.duel_strength, DUEL_STRENGTH_RANGE),
+    }
+}
+
+/// Traits as a single line, or `None` for neutral genes.
+pub fn format_traits(traits: &PigTraits) -> Option<String> {
+    let parts: Vec<String> = [
+        ("рост", traits.growth_bias),
+        ("стойкость", traits.loss_resistance),
+        ("сила", traits.duel_strength),
+    ]
+    .iter()
+    .filter(|(_, value)| value.abs() >= 0.0005)
+    .map(|(name, value)| format!("{} {:+.1}%", name, value * 100.0))
+    .collect();
+
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+impl super::PigGameModule {
+    /// `/breed` in reply or with the partner pig's name: proposes a litter.
+    pub async fn handle_breed_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let pigs = async {
+            let own = db.get_pig(chat_id, user_id).await?;
+            let partner = self.resolve_target_pig(&msg, &args.join(" "), db).await?;
+            let last_bred = match (&own, &partner) {
+                (Some(own), Some(partner)) => Some((db.get_last_bred(own.id).await?, db.get_last_bred(partner.id).await?)),
+                _ => None,
+            };
+            Ok::<_, sqlx::Error>((own, partner, last_bred))
+        };
+
+        let (own, partner, (own_bred, partner_bred)) = match pigs.await {
+            Ok((Some(own), Some(partner), Some(last_bred))) => (own, partner, last_bred),
+            Ok((None, _, _)) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Ok(_) => {
+                bot.send_message(msg.chat.id, "С кем спаривать? Ответь на сообщение хозяина свиньи или укажи её имя")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if partner.user_id == user_id {
+            bot.send_message(msg.chat.id, "Сам с собой не размножишься, как бы ни старался").reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        if own.salo < config.game.breed_cost {
+            bot.send_message(msg.chat.id, format!("🥓 Случка стоит {} сала, а у вас {}", config.game.breed_cost, own.salo))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let ready_at = own_bred.max(partner_bred) + (config.game.breed_delay * 3600) as f64;
+        if ready_at > now {
+            bot.send_message(
+                msg.chat.id,
+                format!("😮‍💨 Свиньи ещё не отошли от прошлого раза. Подождите {}", format_duration((ready_at - now) as u64)),
+            )
+            .reply_to(msg.id)
+            .await?;
+            return Ok(());
+        }
+
+        let expires_at = now as i64 + (config.game.trade_offer_ttl * 60) as i64;
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "💞 {} хочет повязать своего {} с {}\n💰 Цена: {} 🥓\n{}, согласен? У тебя {} мин.",
+                own.owner_name, own.name, partner.name, config.game.breed_cost, partner.owner_name, config.game.trade_offer_ttl
+            ),
+        )
+        .reply_markup(self.create_breed_keyboard(user_id, partner.user_id, expires_at))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Handles `breed:<initiator>:<partner>:<expires_at>:a|d`. Only the partner
+    /// may accept; either side may decline.
+    pub async fn handle_breed_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let (Some(initiator_id), Some(partner_id), Some(expires_at)) =
+            (data.get::<i64>(0), data.get::<i64>(1), data.get::<f64>(2))
+        else {
+            return answer_stale(bot, query).await;
+        };
+        let accept = data.field(3) == Some("a");
+
+        let allowed = user_id == partner_id || (user_id == initiator_id && !accept);
+        if !allowed {
+            bot.answer_callback_query(&query.id).text("🖕🤣 Тебя не спрашивали!").await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let (text, answer) = if expires_at <= now {
+            ("⌛ Свиньи так и не дождались друг друга".to_string(), "Предложение просрочено")
+        } else if !accept {
+            (format!("💔 {} передумал", query.from.full_name()), "Отменено")
+        } else {
+            match self.breed(chat_id, initiator_id, partner_id, now, db, config).await {
+                Ok(Ok(text)) => (text, "Поздравляем с пополнением!"),
+                Ok(Err(failure)) => {
+                    let reason = match failure {
+                        BreedFailure::PigMissing => "❌ Одной из свиней больше нет",
+                        BreedFailure::Cooldown => "😮‍💨 Свиньи ещё не отошли от прошлого раза",
+                        BreedFailure::NotEnoughSalo => "🥓 Не хватило сала на случку",
+                        BreedFailure::StableFull => "🛖 В стойле нет места для поросёнка",
+                    };
+                    (reason.to_string(), "Не получилось")
+                }
+                Err(e) => {
+                    log::error!("Failed to breed pigs: {}", e);
+                    bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        bot.edit_message_text(message.chat().id, message.id(), text).await?;
+        bot.answer_callback_query(&query.id).text(answer).await?;
+        Ok(())
+    }
+
+    /// Mixes the parents' genes and stores the piglet, returning the birth announcement.
+    async fn breed(
+        &self,
+        chat_id: i64,
+        initiator_id: i64,
+        partner_id: i64,
+        now: f64,
+        db: &Database,
+        config: &Config,
+    ) -> Result<Result<String, BreedFailure>, sqlx::Error> {
+        let (Some(initiator), Some(partner)) = (db.get_pig(chat_id, initiator_id).await?, db.get_pig(chat_id, partner_id).await?) else {
+            return Ok(Err(BreedFailure::PigMissing));
+        };
+
+        let traits = mix_traits(
+            &db.get_pig_traits(initiator.id).await?,
+            &db.get_pig_traits(partner.id).await?,
+            &mut rand::rng(),
+        );
+        let terms = BreedTerms {
+            cost: config.game.breed_cost,
+            cooldown_secs: (config.game.breed_delay * 3600) as f64,
+            max_piglets: MAX_PIGLETS,
+        };
+        let name = self.generate_default_pig_name();
+
+        let piglet = match db.breed_pigs(initiator.id, partner.id, &name, traits, terms, now).await? {
+            Ok(piglet) => piglet,
+            Err(failure) => return Ok(Err(failure)),
+        };
+
+        let genes = format_traits(&piglet.traits).unwrap_or_else(|| "обычные".to_string());
+        Ok(Ok(format!(
+            "🐽 У {} и {} родился поросёнок {}!\n🧬 Гены: {}\nОн ждёт {} в /piglets",
+            initiator.name, partner.name, piglet.name, genes, initiator.owner_name
+        )))
+    }
+
+    /// `/piglets`: the caller's stable.
+    pub async fn handle_piglets_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        match db.get_piglets(chat_id, user_id).await {
+            Ok(piglets) if piglets.is_empty() => {
+                bot.send_message(msg.chat.id, "🛖 Стойло пустое. Заведите поросят через /breed")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Ok(piglets) => {
+                let mut lines = vec![format!("🛖 Стойло ({}/{}):", piglets.len(), MAX_PIGLETS)];
+                for (i, piglet) in piglets.iter().enumerate() {
+                    lines.push(format!(
+                        "{}. 🐽 {} ({} × {})\n   🧬 {}",
+                        i + 1,
+                        piglet.name,
+                        piglet.parent_a,
+                        piglet.parent_b,
+                        format_traits(&piglet.traits).unwrap_or_else(|| "обычные гены".to_string())
+                    ));
+                }
+                lines.push("\nВырастить поросёнка в свинарнике: /raise <номер>".to_string());
+                bot.send_message(msg.chat.id, lines.join("\n")).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `/raise <number>`: moves a piglet from /piglets into the pigsty, genes and all.
+    pub async fn handle_raise_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+        let username = msg
+            .from
+            .as_ref()
+            .and_then(|u| u.username.as_deref())
+            .unwrap_or("Unknown");
+
+        let Some(number) = command.integer(0).and_then(|n| usize::try_from(n).ok()).filter(|n| *n > 0) else {
+            bot.send_message(msg.chat.id, "Укажите номер поросёнка из /piglets").reply_to(msg.id).await?;
+            return Ok(());
+        };
+
+        let loaded = async {
+            let piglets = db.get_piglets(chat_id, user_id).await?;
+            let pigs = db.get_stable_pigs(chat_id, user_id).await?;
+            Ok::<_, sqlx::Error>((piglets, pigs))
+        };
+
+        let (piglets, pigs) = match loaded.await {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let Some(piglet) = piglets.get(number - 1) else {
+            bot.send_message(msg.chat.id, format!("В стойле нет поросёнка №{}. Смотрите /piglets", number))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let max_pigs = self.max_pigs(&pigs, config);
+        let text = match db.raise_piglet(piglet.id, user_id, username, max_pigs, unix_now()).await {
+            Ok(Ok(pig)) => format!("🐷 {} вырос и переехал в свинарник! Выбрать его можно через /select", pig.name),
+            Ok(Err(RaiseFailure::PigletMissing)) => "❌ Этого поросёнка уже нет в стойле".to_string(),
+            Ok(Err(RaiseFailure::PigstyFull)) => {
+                format!("🛖 В свинарнике нет места (максимум {}). Улучши свинарник через /build", max_pigs)
+            }
+            Err(e) => {
+                log::error!("Failed to raise piglet: {}", e);
+                "Ошибка базы данных".to_string()
+            }
+        };
+        bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn mixed_traits_stay_in_range() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let strong = PigTraits { growth_bias: 0.3, loss_resistance: 0.3, duel_strength: 0.3 };
+        let weak = PigTraits { growth_bias: -0.1, loss_resistance: 0.0, duel_strength: -0.1 };
+        for _ in 0..200 {
+            let child = mix_traits(&strong, &weak, &mut rng);
+            assert!((GROWTH_BIAS_RANGE.0..=GROWTH_BIAS_RANGE.1).contains(&child.growth_bias));
+            assert!((LOSS_RESISTANCE_RANGE.0..=LOSS_RESISTANCE_RANGE.1).contains(&child.loss_resistance));
+            assert!((DUEL_STRENGTH_RANGE.0..=DUEL_STRENGTH_RANGE.1).contains(&child.duel_strength));
+        }
+    }
+
+    #[test]
+    fn identical_parents_only_drift_by_mutation() {
+        let mut rng = StdRng::seed_from_u64(42);
+        let parent = PigTraits { growth_bias: 0.1, loss_resistance: 0.1, duel_strength: 0.1 };
+        for _ in 0..200 {
+            let child = mix_traits(&parent, &parent, &mut rng);
+            assert!((child.growth_bias - 0.1).abs() <= MUTATION_SPREAD + 1e-9);
+        }
+    }
+
+    #[test]
+    fn neutral_traits_are_not_shown() {
+        assert_eq!(format_traits(&PigTraits::default()), None);
+        let traits = PigTraits { growth_bias: 0.05, ..Default::default() };
+        assert_eq!(format_traits(&traits).as_deref(), Some("рост +5.0%"));
+    }
+}
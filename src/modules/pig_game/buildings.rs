@@ -0,0 +1,270 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, Message, MaybeInaccessibleMessage},
+};
+use crate::config::{BuildingConfig, BuildingsConfig, Config};
+use crate::database::{Database, Pig};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::events::GameEvent;
+use super::helpers::unix_now;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Building {
+    Barn,
+    Pigsty,
+    Vetclinic,
+    Biolab,
+    Butchery,
+    Factory,
+    Warehouse,
+    Institute,
+}
+
+impl Building {
+    pub const ALL: [Building; 8] = [
+        Building::Barn,
+        Building::Pigsty,
+        Building::Vetclinic,
+        Building::Biolab,
+        Building::Butchery,
+        Building::Factory,
+        Building::Warehouse,
+        Building::Institute,
+    ];
+
+    /// Config key, callback payload and `farms` column name.
+    pub fn key(self) -> &'static str {
+        match self {
+            Building::Barn => "barn",
+            Building::Pigsty => "pigsty",
+            Building::Vetclinic => "vetclinic",
+            Building::Biolab => "biolab",
+            Building::Butchery => "butchery",
+            Building::Factory => "factory",
+            Building::Warehouse => "warehouse",
+            Building::Institute => "institute",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|b| b.key() == key)
+    }
+
+    pub fn level(self, pig: &Pig) -> i32 {
+        match self {
+            Building::Barn => pig.barn,
+            Building::Pigsty => pig.pigsty,
+            Building::Vetclinic => pig.vetclinic,
+            Building::Biolab => pig.biolab,
+            Building::Butchery => pig.butchery,
+            Building::Factory => pig.factory,
+            Building::Warehouse => pig.warehouse,
+            Building::Institute => pig.institute,
+        }
+    }
+
+    pub fn config(self, buildings: &BuildingsConfig) -> &BuildingConfig {
+        match self {
+            Building::Barn => &buildings.barn,
+            Building::Pigsty => &buildings.pigsty,
+            Building::Vetclinic => &buildings.vetclinic,
+            Building::Biolab => &buildings.biolab,
+            Building::Butchery => &buildings.butchery,
+            Building::Factory => &buildings.factory,
+            Building::Warehouse => &buildings.warehouse,
+            Building::Institute => &buildings.institute,
+        }
+    }
+
+    /// Whether `EFFECT_PER_LEVEL` is a fraction shown as a percentage rather
+    /// than a plain count.
+    fn is_percent(self) -> bool {
+        !matches!(self, Building::Pigsty | Building::Vetclinic | Building::Warehouse)
+    }
+}
+
+impl super::PigGameModule {
+    /// Salo price of raising a building from `level` to `level + 1`.
+    pub fn building_upgrade_cost(&self, building_config: &BuildingConfig, level: i32) -> i32 {
+        (building_config.base_cost as f64 * building_config.cost_multiplier.powi(level)).round() as i32
+    }
+
+    /// Total effect of a building at the pig's current level.
+    pub fn building_effect(&self, building: Building, pig: &Pig, config: &Config) -> f64 {
+        building.config(&config.buildings).effect_per_level * building.level(pig) as f64
+    }
+
+    /// Inventory capacity: `MAX_ITEMS` plus the warehouse bonus.
+    pub fn max_items(&self, pig: &Pig, config: &Config) -> i64 {
+        config.game.max_items as i64 + self.building_effect(Building::Warehouse, pig, config).round() as i64
+    }
+
+    pub fn salo_multiplier(&self, pig: &Pig, config: &Config) -> f64 {
+        1.0 + self.building_effect(Building::Butchery, pig, config)
+    }
+
+    /// Length of one salo production period, shortened by the factory.
+    pub fn salo_period_secs(&self, pig: &Pig, config: &Config) -> f64 {
+        let speedup = self.building_effect(Building::Factory, pig, config).min(0.75);
+        (config.game.salo_delay * 3600) as f64 * (1.0 - speedup)
+    }
+
+    fn format_building_effect(&self, building: Building, value: f64) -> String {
+        if building.is_percent() {
+            format!("+{}%", (value * 100.0).round())
+        } else {
+            format!("+{}", value.round())
+        }
+    }
+
+    pub fn format_buildings_info(&self, pig: &Pig, config: &Config) -> String {
+        let mut lines = vec![format!("🏗 Постройки {}\n🥓 Сало: {}\n", pig.name, pig.salo)];
+
+        for building in Building::ALL {
+            let building_config = building.config(&config.buildings);
+            let level = building.level(pig);
+            let current = self.format_building_effect(building, building_config.effect_per_level * level as f64);
+
+            let next = if level >= building_config.max_level {
+                "макс. уровень".to_string()
+            } else {
+                format!(
+                    "→ {} за {} 🥓",
+                    self.format_building_effect(building, building_config.effect_per_level * (level + 1) as f64),
+                    self.building_upgrade_cost(building_config, level)
+                )
+            };
+
+            lines.push(format!(
+                "{} {} (ур. {}): {} {} {}",
+                building_config.icon, building_config.name, level, current, building_config.description, next
+            ));
+        }
+
+        lines.join("\n")
+    }
+
+    pub async fn handle_build_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        match db.get_pig(chat_id, user_id).await {
+            Ok(Some(pig)) => {
+                bot.send_message(msg.chat.id, self.format_buildings_info(&pig, config))
+                    .reply_markup(self.create_build_keyboard(&pig, config))
+                    .await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `build:<user_id>:<building>` presses.
+    pub async fn handle_build_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let Some(original_user_id) = data.get::<i64>(0) else {
+            return answer_stale(bot, query).await;
+        };
+        let building = data.field(1).and_then(Building::from_key);
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id)
+                .text("🖕🤣 Это не твоя стройка!")
+                .await?;
+            return Ok(());
+        }
+
+        let Some(building) = building else {
+            bot.answer_callback_query(&query.id)
+                .text("Неизвестная постройка")
+                .await?;
+            return Ok(());
+        };
+
+        let pig = match db.get_pig(chat_id, user_id).await {
+            Ok(Some(pig)) => pig,
+            Ok(None) => {
+                bot.answer_callback_query(&query.id)
+                    .text("У вас нет свиньи!")
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id)
+                    .text("Ошибка базы данных")
+                    .await?;
+                return Ok(());
+            }
+        };
+
+        let building_config = building.config(&config.buildings);
+        let level = building.level(&pig);
+        if level >= building_config.max_level {
+            bot.answer_callback_query(&query.id)
+                .text(format!("{} уже максимального уровня", building_config.name))
+                .await?;
+            return Ok(());
+        }
+
+        let cost = self.building_upgrade_cost(building_config, level);
+        if pig.salo < cost {
+            bot.answer_callback_query(&query.id)
+                .text(format!("Не хватает сала: нужно {} 🥓, у вас {}", cost, pig.salo))
+                .show_alert(true)
+                .await?;
+            return Ok(());
+        }
+
+        match db.upgrade_building(chat_id, user_id, building.key(), level, cost, unix_now()).await {
+            Ok(Some(upgraded)) => {
+                bot.answer_callback_query(&query.id)
+                    .text(format!("{} {} улучшен до уровня {}", building_config.icon, building_config.name, level + 1))
+                    .await?;
+                bot.edit_message_text(message.chat().id, message.id(), self.format_buildings_info(&upgraded, config))
+                    .reply_markup(self.create_build_keyboard(&upgraded, config))
+                    .await?;
+                self.emit_event(bot, message.chat().id, user_id, GameEvent::BuildingUpgraded, db, config).await;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id)
+                    .text("Не получилось построить, попробуйте ещё раз")
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to upgrade building: {}", e);
+                bot.answer_callback_query(&query.id)
+                    .text("Ошибка базы данных")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
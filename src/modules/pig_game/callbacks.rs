@@ -1,143 +1,183 @@
 use teloxide::types::MessageId;
 use teloxide::{
     prelude::*,
-    types::{ChatMemberStatus, CallbackQuery}
+    types::CallbackQuery
 };
 use crate::database::Database;
 use crate::config::Config;
+use crate::modules::callback::{CallbackData, answer_stale};
+use crate::modules::permissions::is_chat_admin;
+use super::cooldown::FeedOutcome;
+use super::events::GameEvent;
 
 impl super::PigGameModule {
     pub async fn handle_callback_query(
         &self,
         bot: Bot,
         query: CallbackQuery,
+        data: &CallbackData,
         db: &Database,
         config: &Config,
     ) -> ResponseResult<()> {
-        if let Some(data) = query.data {
-            if let Some(message) = &query.message {
-                let chat_id = message.chat().id.0;
-                let user_id = query.from.id.0 as i64;
-
-                match data.as_str() {
-                    data if data.starts_with("grow:") => {
-                        let original_user_id: i64 = data.strip_prefix("grow:").unwrap().parse().unwrap_or(0);
-
-                        if user_id != original_user_id {
-                            bot.answer_callback_query(&query.id)
-                                .text("🖕🤣 Это не твой гров!")
-                                .await?;
-                            return Ok(());
-                        }
+        let Some(message) = &query.message else {
+            return answer_stale(&bot, &query).await;
+        };
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
 
-                        match db.get_pig(chat_id, user_id).await {
-                            Ok(Some(mut pig)) => {
-                                match self.feed_pig(&mut pig, db, config).await {
-                                    Ok(grow_message) => {
-                                        bot.edit_message_text(
-                                            message.chat().id,
-                                            message.id(),
-                                            grow_message,
-                                        )
-                                        .reply_markup(self.create_grow_keyboard(user_id))
-                                        .await?;
-                                    }
-                                    Err(e) => {
-                                        log::error!("Failed to feed pig: {}", e);
-                                        bot.answer_callback_query(&query.id)
-                                            .text("Ошибка при кормлении свиньи")
-                                            .await?;
-                                    }
-                                }
-                            }
-                            Ok(None) => {
+        match data.namespace() {
+            "grow" => {
+                let Some(original_user_id) = data.get::<i64>(0) else {
+                    return answer_stale(&bot, &query).await;
+                };
+
+                if user_id != original_user_id {
+                    bot.answer_callback_query(&query.id)
+                        .text("🖕🤣 Это не твой гров!")
+                        .await?;
+                    return Ok(());
+                }
+
+                match db.get_pig(chat_id, user_id).await {
+                    Ok(Some(mut pig)) => {
+                        match self.feed_pig(&mut pig, db, config, false).await {
+                            Ok(FeedOutcome::Cooldown(remaining)) => {
                                 bot.answer_callback_query(&query.id)
-                                    .text("У вас нет свиньи!")
+                                    .text(self.format_cooldown_message(&pig.name, remaining))
+                                    .show_alert(true)
                                     .await?;
+                                return Ok(());
+                            }
+                            Ok(FeedOutcome::Fed { message: grow_message, growth }) => {
+                                bot.edit_message_text(
+                                    message.chat().id,
+                                    message.id(),
+                                    grow_message,
+                                )
+                                .reply_markup(self.create_grow_keyboard(user_id))
+                                .await?;
+                                self.emit_event(&bot, message.chat().id, user_id, GameEvent::Fed { growth }, db, config).await;
                             }
                             Err(e) => {
-                                log::error!("Database error: {}", e);
+                                log::error!("Failed to feed pig: {}", e);
                                 bot.answer_callback_query(&query.id)
-                                    .text("Ошибка базы данных")
+                                    .text("Ошибка при кормлении свиньи")
                                     .await?;
                             }
                         }
                     }
+                    Ok(None) => {
+                        bot.answer_callback_query(&query.id)
+                            .text("У вас нет свиньи!")
+                            .await?;
+                    }
+                    Err(e) => {
+                        log::error!("Database error: {}", e);
+                        bot.answer_callback_query(&query.id)
+                            .text("Ошибка базы данных")
+                            .await?;
+                    }
+                }
+            }
 
-                    data if data.starts_with("remove:") => {
-                        let chat_id = message.chat().id;
-                        let callback_message_id = message.id();
+            "build" => {
+                return self.handle_build_callback(&bot, &query, message, data, db, config).await;
+            }
 
-                        let bot_user = bot.get_me().await?;
-                        let bot_member = bot.get_chat_member(chat_id, bot_user.id).await;
+            "inv" | "eq" => {
+                return self.handle_inventory_callback(&bot, &query, message, data, db, config).await;
+            }
 
+            "top" => {
+                return self.handle_top_callback(&bot, &query, message, data, db).await;
+            }
+            "hof" => {
+                return self.handle_hall_of_fame_callback(&bot, &query, message, data, db).await;
+            }
+            "quests" => {
+                return self.handle_quests_callback(&bot, &query, message, data, db, config).await;
+            }
+            "breed" => {
+                return self.handle_breed_callback(&bot, &query, message, data, db, config).await;
+            }
+            "select" => {
+                return self.handle_select_callback(&bot, &query, message, data, db, config).await;
+            }
+            "slaughter" => {
+                return self.handle_slaughter_callback(&bot, &query, message, data, db, config).await;
+            }
+            "market" => {
+                return self.handle_market_callback(&bot, &query, message, data, db, config).await;
+            }
+            "trade" => {
+                return self.handle_offer_callback(&bot, &query, message, data, db, config).await;
+            }
 
-                        let can_delete = match bot_member {
-                            Ok(member) => match member.status() {
-                                ChatMemberStatus::Owner => true,
-                                ChatMemberStatus::Administrator { .. } => true, // assume admin can delete
-                                _ => false,
-                            },
-                            Err(_) => false,
-                        };
+            "duel" => {
+                return self.handle_duel_callback(&bot, &query, message, data, db, config).await;
+            }
 
-                        bot.delete_message(
-                            message.chat().id,
-                            callback_message_id,
-                        ).await?;
-
-                        if can_delete {
-                            if let Some(original_msg_id_str) = data.strip_prefix("remove:") {
-                                if let Ok(id) = original_msg_id_str.parse::<i32>() {
-                                    let original_msg_id = MessageId(id);
-                                    bot.delete_message(message.chat().id, original_msg_id).await.ok();
-                                }
-                            }
-                        }
-                    }
+            "remove" => {
+                let chat_id = message.chat().id;
+                let callback_message_id = message.id();
 
-                    data if data.starts_with("back:") => {
-                        let original_user_id: i64 = data.strip_prefix("back:").unwrap().parse().unwrap_or(0);
-                        if user_id != original_user_id {
-                            bot.answer_callback_query(&query.id)
-                                .text("🖕🤣 Это не твой хряк!")
-                                .await?;
-                            return Ok(());
-                        }
+                let bot_user = bot.get_me().await?;
+                // assume admin can delete
+                let can_delete = is_chat_admin(&bot, config, chat_id, bot_user.id).await;
 
-                        match db.get_pig(chat_id, user_id).await {
-                            Ok(Some(pig)) => {
-                                let response_text = self.format_pig_info(&pig, db).await;
+                bot.delete_message(
+                    message.chat().id,
+                    callback_message_id,
+                ).await?;
 
-                                bot.edit_message_text(
-                                    message.chat().id,
-                                    message.id(),
-                                    response_text,
-                                )
-                                .reply_markup(self.create_pig_keyboard(user_id, message.id().0))
-                                .await?;
-                            }
-                            Ok(None) => {
-                                bot.answer_callback_query(&query.id)
-                                    .text("У вас нет свиньи!")
-                                    .await?;
-                            }
-                            Err(e) => {
-                                log::error!("Database error: {}", e);
-                                bot.answer_callback_query(&query.id)
-                                    .text("Ошибка базы данных")
-                                    .await?;
-                            }
-                        }
-                    }
+                if can_delete
+                    && let Some(id) = data.get::<i32>(0)
+                {
+                    let original_msg_id = MessageId(id);
+                    bot.delete_message(message.chat().id, original_msg_id).await.ok();
+                }
+            }
+
+            "back" => {
+                let Some(original_user_id) = data.get::<i64>(0) else {
+                    return answer_stale(&bot, &query).await;
+                };
+                if user_id != original_user_id {
+                    bot.answer_callback_query(&query.id)
+                        .text("🖕🤣 Это не твой хряк!")
+                        .await?;
+                    return Ok(());
+                }
 
-                    _ => {
+                match db.get_pig(chat_id, user_id).await {
+                    Ok(Some(pig)) => {
+                        let response_text = self.format_pig_info(&pig, db).await;
+                        let equipment = self.equipment_stats(&pig, db).await;
+                        let cooldown_remaining = self.feed_cooldown_remaining(&pig, &equipment, &config.game);
+
+                        bot.edit_message_text(
+                            message.chat().id,
+                            message.id(),
+                            response_text,
+                        )
+                        .reply_markup(self.create_pig_keyboard(user_id, message.id().0, cooldown_remaining))
+                        .await?;
+                    }
+                    Ok(None) => {
                         bot.answer_callback_query(&query.id)
-                            .text("Неизвестная команда")
+                            .text("У вас нет свиньи!")
+                            .await?;
+                    }
+                    Err(e) => {
+                        log::error!("Database error: {}", e);
+                        bot.answer_callback_query(&query.id)
+                            .text("Ошибка базы данных")
                             .await?;
                     }
                 }
             }
+
+            _ => return answer_stale(&bot, &query).await,
         }
 
         bot.answer_callback_query(&query.id).await?;
@@ -0,0 +1,160 @@
+//! Minimal raster line chart rendered straight into a PNG, so /history needs
+//! no fonts, plotting libraries or external services.
+
+type Rgb = [u8; 3];
+
+const BACKGROUND: Rgb = [255, 250, 245];
+const GRID: Rgb = [230, 222, 215];
+const AXIS: Rgb = [90, 80, 75];
+const AREA: Rgb = [250, 214, 222];
+const LINE: Rgb = [220, 70, 110];
+const MARGIN: usize = 24;
+
+struct Canvas {
+    width: usize,
+    height: usize,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize, color: Rgb) -> Self {
+        let pixels = color.iter().copied().cycle().take(width * height * 3).collect();
+        Self { width, height, pixels }
+    }
+
+    fn set(&mut self, x: i64, y: i64, color: Rgb) {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return;
+        }
+        let offset = (y as usize * self.width + x as usize) * 3;
+        self.pixels[offset..offset + 3].copy_from_slice(&color);
+    }
+
+    fn dot(&mut self, x: i64, y: i64, radius: i64, color: Rgb) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                if dx * dx + dy * dy <= radius * radius {
+                    self.set(x + dx, y + dy, color);
+                }
+            }
+        }
+    }
+
+    /// Bresenham line stamped with a dot of `radius` for thickness.
+    fn line(&mut self, (x0, y0): (i64, i64), (x1, y1): (i64, i64), radius: i64, color: Rgb) {
+        let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+        let (sx, sy) = (if x0 < x1 { 1 } else { -1 }, if y0 < y1 { 1 } else { -1 });
+        let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+
+        loop {
+            self.dot(x, y, radius, color);
+            if x == x1 && y == y1 {
+                break;
+            }
+            let e2 = 2 * err;
+            if e2 >= dy {
+                err += dy;
+                x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                y += sy;
+            }
+        }
+    }
+
+    fn encode_png(&self) -> Result<Vec<u8>, png::EncodingError> {
+        let mut out = Vec::new();
+        {
+            let mut encoder = png::Encoder::new(&mut out, self.width as u32, self.height as u32);
+            encoder.set_color(png::ColorType::Rgb);
+            encoder.set_depth(png::BitDepth::Eight);
+            let mut writer = encoder.write_header()?;
+            writer.write_image_data(&self.pixels)?;
+        }
+        Ok(out)
+    }
+}
+
+/// Renders `points` as `(x, y)` pairs into a PNG line chart with a filled area
+/// and horizontal grid lines. Points should be sorted by `x`.
+pub fn render_line_chart(points: &[(f64, f64)], width: usize, height: usize) -> Result<Vec<u8>, png::EncodingError> {
+    let mut canvas = Canvas::new(width, height, BACKGROUND);
+
+    let (left, top) = (MARGIN as i64, MARGIN as i64);
+    let (right, bottom) = ((width - MARGIN) as i64, (height - MARGIN) as i64);
+
+    for i in 0..=4 {
+        let y = top + (bottom - top) * i / 4;
+        canvas.line((left, y), (right, y), 0, GRID);
+    }
+
+    if !points.is_empty() {
+        let (min_x, max_x) = bounds(points.iter().map(|p| p.0));
+        let (mut min_y, mut max_y) = bounds(points.iter().map(|p| p.1));
+        let pad = ((max_y - min_y) * 0.1).max(1.0);
+        min_y -= pad;
+        max_y += pad;
+
+        let project = |(x, y): (f64, f64)| {
+            let px = if max_x > min_x {
+                left as f64 + (x - min_x) / (max_x - min_x) * (right - left) as f64
+            } else {
+                (left + right) as f64 / 2.0
+            };
+            let py = bottom as f64 - (y - min_y) / (max_y - min_y) * (bottom - top) as f64;
+            (px.round() as i64, py.round() as i64)
+        };
+        let projected: Vec<(i64, i64)> = points.iter().copied().map(project).collect();
+
+        for pair in projected.windows(2) {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            for x in x0..=x1 {
+                let t = if x1 > x0 { (x - x0) as f64 / (x1 - x0) as f64 } else { 0.0 };
+                let y = (y0 as f64 + t * (y1 - y0) as f64).round() as i64;
+                canvas.line((x, y), (x, bottom), 0, AREA);
+            }
+        }
+        for pair in projected.windows(2) {
+            canvas.line(pair[0], pair[1], 1, LINE);
+        }
+        for &(x, y) in &projected {
+            canvas.dot(x, y, 3, LINE);
+        }
+    }
+
+    canvas.line((left, top), (left, bottom), 0, AXIS);
+    canvas.line((left, bottom), (right, bottom), 0, AXIS);
+
+    canvas.encode_png()
+}
+
+fn bounds(values: impl Iterator<Item = f64>) -> (f64, f64) {
+    values.fold((f64::MAX, f64::MIN), |(lo, hi), v| (lo.min(v), hi.max(v)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn decoded_size(data: &[u8]) -> (u32, u32) {
+        let decoder = png::Decoder::new(data);
+        let reader = decoder.read_info().expect("valid png");
+        let info = reader.info();
+        (info.width, info.height)
+    }
+
+    #[test]
+    fn renders_decodable_png_of_requested_size() {
+        let points = [(0.0, 10.0), (1.0, 25.0), (2.0, 18.0), (3.0, 40.0)];
+        let png = render_line_chart(&points, 320, 200).unwrap();
+        assert_eq!(decoded_size(&png), (320, 200));
+    }
+
+    #[test]
+    fn handles_flat_and_single_point_series() {
+        assert!(render_line_chart(&[(5.0, 7.0)], 100, 80).is_ok());
+        assert!(render_line_chart(&[(0.0, 7.0), (1.0, 7.0)], 100, 80).is_ok());
+        assert!(render_line_chart(&[], 100, 80).is_ok());
+    }
+}
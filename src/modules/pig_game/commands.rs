@@ -4,8 +4,15 @@ use teloxide::{
     sugar::request::RequestReplyExt,
     types::Message,
 };
-use crate::database::Database;
+use crate::database::{Database, ItemStats};
 use crate::config::Config;
+use crate::modules::command::ParsedCommand;
+use super::breeding::format_traits;
+use super::cooldown::FeedOutcome;
+use super::events::GameEvent;
+use super::helpers::{roll_growth, unix_now};
+use super::pills::PillEffect;
+use super::world_events::{apply_world_events, WorldEvent};
 
 
 impl super::PigGameModule {
@@ -13,11 +20,11 @@ impl super::PigGameModule {
         &self,
         bot: Bot,
         msg: Message,
-        command: &str,
-        args: Vec<&str>,
+        command: &ParsedCommand,
         db: &Database,
         config: &Config,
     ) -> ResponseResult<()> {
+        let args = command.words();
         let chat_id = msg.chat.id.0;
         let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
         let username = msg
@@ -27,7 +34,7 @@ impl super::PigGameModule {
             .map(|s| s.as_str())
             .unwrap_or("Unknown");
 
-        match command {
+        match command.name {
             "pig" => {
                 let pig_name = if args.is_empty() {
                     self.generate_default_pig_name()
@@ -36,11 +43,14 @@ impl super::PigGameModule {
                 };
 
                 match db.get_pig(chat_id, user_id).await {
+                    Ok(Some(_)) if !args.is_empty() => {
+                        return self.handle_extra_pig(bot, msg, &pig_name, db, config).await;
+                    }
                     Ok(Some(existing_pig)) => {
                         bot.send_message(
                             msg.chat.id,
                             format!(
-                                "У вас уже есть свинья: {} (вес: {})",
+                                "У вас уже есть свинья: {} (вес: {})\nЕщё одну можно завести через /pig <имя>, если есть место в свинарнике",
                                 existing_pig.name, existing_pig.weight
                             ),
                         )
@@ -75,7 +85,7 @@ impl super::PigGameModule {
                 }
             }
 
-            "grow" | "гров" => {
+            "grow" => {
                 let pig_name = if args.is_empty() {
                     self.generate_default_pig_name()
                 } else {
@@ -83,9 +93,15 @@ impl super::PigGameModule {
                 };
 
                 match db.get_pig(chat_id, user_id).await {
-                    Ok(Some(mut pig)) => match self.feed_pig(&mut pig, db, config).await {
-                        Ok(message) => {
+                    Ok(Some(mut pig)) => match self.feed_pig(&mut pig, db, config, false).await {
+                        Ok(FeedOutcome::Fed { message, growth }) => {
                             bot.send_message(msg.chat.id, message).await?;
+                            self.emit_event(&bot, msg.chat.id, user_id, GameEvent::Fed { growth }, db, config).await;
+                        }
+                        Ok(FeedOutcome::Cooldown(remaining)) => {
+                            bot.send_message(msg.chat.id, self.format_cooldown_message(&pig.name, remaining))
+                                .reply_to(msg.id)
+                                .await?;
                         }
                         Err(e) => {
                             log::error!("Failed to feed pig: {}", e);
@@ -96,9 +112,15 @@ impl super::PigGameModule {
                     Ok(None) => {
                         match self.create_new_pig(chat_id, user_id, username, &pig_name, db).await {
                             Ok(mut pig) => {
-                                match self.feed_pig(&mut pig, db, config).await {
-                                    Ok(message) => {
+                                match self.feed_pig(&mut pig, db, config, false).await {
+                                    Ok(FeedOutcome::Fed { message, growth }) => {
                                         bot.send_message(msg.chat.id, message).await?;
+                                        self.emit_event(&bot, msg.chat.id, user_id, GameEvent::Fed { growth }, db, config).await;
+                                    }
+                                    Ok(FeedOutcome::Cooldown(remaining)) => {
+                                        bot.send_message(msg.chat.id, self.format_cooldown_message(&pig.name, remaining))
+                                            .reply_to(msg.id)
+                                            .await?;
                                     }
                                     Err(e) => {
                                         log::error!("Failed to feed pig: {}", e);
@@ -124,8 +146,10 @@ impl super::PigGameModule {
             "my" => match db.get_pig(chat_id, user_id).await {
                 Ok(Some(pig)) => {
                     let message = self.format_pig_info(&pig, db).await;
+                    let equipment = self.equipment_stats(&pig, db).await;
+                    let cooldown_remaining = self.feed_cooldown_remaining(&pig, &equipment, &config.game);
                     bot.send_message(msg.chat.id, message)
-                        .reply_markup(self.create_pig_keyboard(user_id, msg.id.0))
+                        .reply_markup(self.create_pig_keyboard(user_id, msg.id.0, cooldown_remaining))
                         .await?;
                 }
                 Ok(None) => {
@@ -191,89 +215,156 @@ impl super::PigGameModule {
             }
 
             "top" => {
-                match db.get_chat_pigs_ranked(chat_id).await {
-                    Ok(pigs) => {
-                        if pigs.is_empty() {
-                            bot.send_message(msg.chat.id, "В этом чате пока нет свиней 🐖").await?;
-                        } else {
-                            let top_pigs: Vec<String> = pigs
-                                .iter()
-                                .take(5)
-                                .enumerate()
-                                .map(|(i, pig)| {
-                                    let position = i + 1;
-                                    let medal = match position {
-                                        1 => "🥇",
-                                        2 => "🥈",
-                                        3 => "🥉",
-                                        _ => "🏅",
-                                    };
-                                    format!("{} {}. {} - {} кг (владелец: {}) 🐖", medal, position, pig.name, pig.weight, pig.owner_name)
-                                })
-                                .collect();
-
-                            let message = format!("🏆 Топ 5 свиней в чате:\n{}", top_pigs.join("\n"));
-                            bot.send_message(msg.chat.id, message).await?;
-                        }
-                    }
-                    Err(e) => {
-                        log::error!("Database error: {}", e);
-                        bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
-                    }
-                }
+                self.handle_top_command(bot, msg, args, db).await?;
+            }
+
+            "globaltop" => {
+                self.handle_global_top_command(bot, msg, args, db, config).await?;
+            }
+
+            "profile" => {
+                self.handle_profile_command(bot, msg, db).await?;
+            }
+
+            "achievements" => {
+                self.handle_achievements_command(bot, msg, args, db).await?;
+            }
+
+            "breed" => {
+                self.handle_breed_command(bot, msg, args, db, config).await?;
+            }
+
+            "piglets" => {
+                self.handle_piglets_command(bot, msg, db).await?;
+            }
+
+            "raise" => {
+                self.handle_raise_command(bot, msg, command, db, config).await?;
+            }
+
+            "select" => {
+                self.handle_select_command(bot, msg, db, config).await?;
+            }
+
+            "slaughter" => {
+                self.handle_slaughter_command(bot, msg, db, config).await?;
+            }
+
+            "graveyard" => {
+                self.handle_graveyard_command(bot, msg, db).await?;
+            }
+
+            "events" => {
+                self.handle_events_command(bot, msg, args, db, config).await?;
+            }
+
+            "quests" => {
+                self.handle_quests_command(bot, msg, args, db, config).await?;
+            }
+
+            "season" => {
+                self.handle_season_command(bot, msg, db, config).await?;
+            }
+
+            "halloffame" => {
+                self.handle_hall_of_fame_command(bot, msg, db).await?;
             }
 
             "name" => {
-                if args.is_empty() {
-                    bot.send_message(msg.chat.id, "Введи имя, еблан").reply_to(msg.id).await?;
+                let new_name = command.text(0).unwrap_or_default().to_string();
+                if new_name.as_str().graphemes(true).count() >= 32 {
+                    bot.send_message(msg.chat.id, "У тебя хряк весит меньше, чем твое имя. Придумай что-то короче 32 буковок, блядина.")
+                        .reply_to(msg.id)
+                        .await?;
                 } else {
-                    let new_name = args.join(" ");
-                    if new_name.as_str().graphemes(true).count() >= 32 {
-                        bot.send_message(msg.chat.id, "У тебя хряк весит меньше, чем твое имя. Придумай что-то короче 32 буковок, блядина.")
-                            .reply_to(msg.id)
-                            .await?;
-                    } else {
-                        match db.get_pig(chat_id, user_id).await {
-                            Ok(Some(_)) => {
-                                match db.update_pig_name(chat_id, user_id, &new_name).await {
-                                    Ok(_) => {
-                                        bot.send_message(msg.chat.id,
-                                            format!("Теперь вашего хряка зовут {}", new_name))
+                    match db.get_pig(chat_id, user_id).await {
+                        Ok(Some(_)) => {
+                            match db.update_pig_name(chat_id, user_id, &new_name).await {
+                                Ok(_) => {
+                                    bot.send_message(msg.chat.id,
+                                        format!("Теперь вашего хряка зовут {}", new_name))
+                                    .reply_to(msg.id)
+                                    .await?;
+                                }
+                                Err(e) => {
+                                    log::error!("Database error: {}", e);
+                                    bot.send_message(msg.chat.id, "Какая-то хуйня случилась. Пиши админу, блять").await?;
+                                }
+                            }
+                        }
+                        Ok(None) => {
+                            let owner_name = msg.from.map(|u| u.full_name()).unwrap_or_else(|| "Unknown".to_string());
+                            match self.create_new_pig(chat_id, user_id, &owner_name, &new_name, db).await {
+                                Ok(_) => {
+                                    bot.send_message(msg.chat.id,
+                                        format!("Создана новая свинья с именем '{}'! 🐷", new_name))
                                         .reply_to(msg.id)
                                         .await?;
-                                    }
-                                    Err(e) => {
-                                        log::error!("Database error: {}", e);
-                                        bot.send_message(msg.chat.id, "Какая-то хуйня случилась. Пиши админу, блять").await?;
-                                    }
                                 }
-                            }
-                            Ok(None) => {
-                                let owner_name = msg.from.map(|u| u.full_name()).unwrap_or_else(|| "Unknown".to_string());
-                                match self.create_new_pig(chat_id, user_id, &owner_name, &new_name, db).await {
-                                    Ok(_) => {
-                                        bot.send_message(msg.chat.id,
-                                            format!("Создана новая свинья с именем '{}'! 🐷", new_name))
-                                            .reply_to(msg.id)
-                                            .await?;
-                                    }
-                                    Err(e) => {
-                                        log::error!("Failed to create pig: {}", e);
-                                        bot.send_message(msg.chat.id, "Ошибка при создании свиньи")
-                                            .reply_to(msg.id)
-                                            .await?;
-                                    }
+                                Err(e) => {
+                                    log::error!("Failed to create pig: {}", e);
+                                    bot.send_message(msg.chat.id, "Ошибка при создании свиньи")
+                                        .reply_to(msg.id)
+                                        .await?;
                                 }
                             }
-                            Err(e) => {
-                                log::error!("Database error: {}", e);
-                                bot.send_message(msg.chat.id, "Какая-то хуйня случилась. Пиши админу, блять").await?;
-                            }
+                        }
+                        Err(e) => {
+                            log::error!("Database error: {}", e);
+                            bot.send_message(msg.chat.id, "Какая-то хуйня случилась. Пиши админу, блять").await?;
                         }
                     }
                 }
             }
 
+            "salo" => {
+                self.handle_salo_command(bot, msg, db, config).await?;
+            }
+
+            "pill" => {
+                self.handle_pill_command(bot, msg, db, config).await?;
+            }
+
+            "poison" => {
+                self.handle_poison_command(bot, msg, args, db, config).await?;
+            }
+
+            "vet" => {
+                self.handle_vet_command(bot, msg, db, config).await?;
+            }
+
+            "inventory" => {
+                self.handle_inventory_command(bot, msg, db, config).await?;
+            }
+
+            "give" | "trade" => {
+                self.handle_offer_command(bot, msg, command, db, config).await?;
+            }
+
+            "sell" => {
+                self.handle_sell_command(bot, msg, command, db, config).await?;
+            }
+
+            "market" => {
+                self.handle_market_command(bot, msg, command, db, config).await?;
+            }
+
+            "duel" => {
+                self.handle_duel_command(bot, msg, command, db, config).await?;
+            }
+
+            "duelstats" => {
+                self.handle_duel_stats_command(bot, msg, args, db).await?;
+            }
+
+            "history" => {
+                self.handle_history_command(bot, msg, args, db).await?;
+            }
+
+            "build" => {
+                self.handle_build_command(bot, msg, db, config).await?;
+            }
+
             _ => {
                 bot.send_message(msg.chat.id, "Неизвестная команда свиньи")
                     .await?;
@@ -293,50 +384,134 @@ impl super::PigGameModule {
         }.unwrap_or(0);
 
 
-        format!(
+        let mut info = format!(
             "🐖 Ваш {} весит {} кг\n\
             📊 Место в топе: {}\n\
+            🥓 Сало: {}\n\
+            💊 Таблетки: {}\n\
             ",
-            pig.name, pig.weight, position,
-        )
+            pig.name, pig.weight, position, pig.salo, pig.pills,
+        );
+        if pig.poisoned {
+            info.push_str("🤢 Отравлен! Вылечите в /vet\n");
+        }
+        match db.get_pig_traits(pig.id).await {
+            Ok(traits) => {
+                if let Some(genes) = format_traits(&traits) {
+                    info.push_str(&format!("🧬 Гены: {}\n", genes));
+                }
+            }
+            Err(e) => log::error!("Database error: {}", e),
+        }
+        match db.get_prestige(pig.chat_id, pig.user_id).await {
+            Ok(prestige) if prestige > 0 => info.push_str(&format!("⭐ Престиж: {}\n", prestige)),
+            Ok(_) => {}
+            Err(e) => log::error!("Database error: {}", e),
+        }
+        if let Some(titles) = self.format_season_titles(pig.chat_id, pig.user_id, db).await {
+            info.push_str(&format!("\n🏆 Титулы:\n{}\n", titles));
+        }
+
+        let equipped = match db.get_equipped_loot(pig.chat_id, pig.user_id).await {
+            Ok(items) => items,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                Vec::new()
+            }
+        };
+        if !equipped.is_empty() {
+            info.push_str("\n🎽 Снаряжение:\n");
+            for item in &equipped {
+                info.push_str(&format!("{} {} ({} {})\n", item.icon, item.name, item.class_icon, item.class_name));
+            }
+            info.push_str(&format!("✨ Итого: {}\n", self.format_item_stats(&ItemStats::total(&equipped))));
+        }
+        info
     }
 
 
-    pub async fn feed_pig(&self, pig: &mut crate::database::Pig, db: &Database, config: &Config) -> Result<String, sqlx::Error> {
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs_f64();
+    pub async fn feed_pig(&self, pig: &mut crate::database::Pig, db: &Database, config: &Config, use_pill: bool) -> Result<FeedOutcome, sqlx::Error> {
+        let current_time = unix_now();
+        let equipment = ItemStats::total(&db.get_equipped_loot(pig.chat_id, pig.user_id).await?);
+        let cooldown = self.feed_cooldown_secs(&equipment, &config.game);
+
+        if !db.try_claim_feed(pig.chat_id, pig.user_id, current_time, cooldown).await? {
+            if let Some(fresh) = db.get_pig(pig.chat_id, pig.user_id).await? {
+                *pig = fresh;
+            }
+            return Ok(FeedOutcome::Cooldown(self.feed_cooldown_remaining(pig, &equipment, &config.game)));
+        }
 
         pig.last_feed = current_time;
 
         let total_players = db.get_chat_total_players(pig.chat_id).await?;
         let current_rank = db.get_pig_rank(pig.chat_id, pig.user_id).await?.unwrap_or(1);
         let score = pig.weight as f64;
-        let (min_grow, max_grow) = self.calculate_grow_range(score, current_rank, total_players, &config.game);
+        let traits = db.get_pig_traits(pig.id).await?;
+        let prestige = db.get_prestige(pig.chat_id, pig.user_id).await?;
+        let mut modifiers = self.grow_modifiers(pig, &equipment, &traits, prestige, config);
+        let events: Vec<WorldEvent> = db
+            .get_active_world_events(pig.chat_id, current_time)
+            .await?
+            .iter()
+            .filter_map(|record| WorldEvent::from_key(&record.kind))
+            .collect();
+        let mut notes = apply_world_events(&mut modifiers, &events, &config.events);
+        let (min_grow, max_grow) = self.calculate_grow_range(score, current_rank, total_players, &modifiers, &config.game);
 
-        let growth = if min_grow == max_grow {
-            min_grow
+        // A poisoned pig can only lose weight until it is cured.
+        let mut growth = if pig.poisoned {
+            notes.push("🤢 Свинья отравлена и всё выблевала. Вылечите её в /vet".to_string());
+            roll_growth(min_grow.min(0), 0)
         } else {
-            let range = (max_grow - min_grow + 1) as u32;
-            let random_offset = rand::random::<u32>() % range;
-            min_grow + random_offset as i32
+            roll_growth(min_grow, max_grow)
         };
+        let mut pills_delta = 0;
+        let mut poison = false;
+
+        if use_pill && pig.pills > 0 {
+            pills_delta -= 1;
+            match self.roll_pill(pig, max_grow, config) {
+                PillEffect::Boost(_) if pig.poisoned => {
+                    notes.push("💊 Таблетка не помогла отравленной свинье".to_string());
+                }
+                PillEffect::Boost(boosted) => {
+                    growth = boosted;
+                    notes.push("💊 Таблетка подействовала, хряка раздуло!".to_string());
+                }
+                PillEffect::Poison => {
+                    poison = true;
+                    notes.push("🤢 Таблетка оказалась палёной, свинья отравилась!".to_string());
+                }
+            }
+        }
+
+        if rand::random::<f64>() < self.pill_drop_chance(pig, config) {
+            pills_delta += 1;
+            notes.push("💊 Свинья нашла в корыте таблетку!".to_string());
+        }
 
-        pig.weight = (pig.weight + growth).max(1);
+        *pig = db.apply_feed(pig.chat_id, pig.user_id, growth, pills_delta, poison, current_time).await?;
+        let gained = pig.weight - pig.last_weight;
 
-        db.update_pig(pig).await?;
+        if let Some(note) = self.roll_loot_drop(pig, db, config).await? {
+            notes.push(note);
+        }
 
-        let growth_text = if growth > 0 {
-            format!("поправился на {} кг", growth)
-        } else if growth < 0 {
-            format!("уменьшился на {} кг", -growth)
+        let growth_text = if gained > 0 {
+            format!("поправился на {} кг", gained)
+        } else if gained < 0 {
+            format!("уменьшился на {} кг", -gained)
         } else {
-            format!("обосрался и нихуя не прибавил")
+            "обосрался и нихуя не прибавил".to_string()
         };
-        Ok(format!("🐖 Ваш {} {} \n\
-                    💪 Теперь он весит {} кг.\n
-                    ",
-                pig.name, growth_text, pig.weight))
+
+        let mut message = format!("🐖 Ваш {} {}\n💪 Теперь он весит {} кг.", pig.name, growth_text, pig.weight);
+        for note in notes {
+            message.push('\n');
+            message.push_str(&note);
+        }
+
+        Ok(FeedOutcome::Fed { message, growth: gained })
     }
 }
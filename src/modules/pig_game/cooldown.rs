@@ -0,0 +1,30 @@
+use crate::config::GameConfig;
+use crate::database::{ItemStats, Pig};
+use super::helpers::{format_duration, unix_now};
+
+/// Result of a feed attempt: either the growth report with the weight actually
+/// gained, or the seconds left until the pig can be fed again.
+pub enum FeedOutcome {
+    Fed { message: String, growth: i32 },
+    Cooldown(u64),
+}
+
+impl super::PigGameModule {
+    /// Feed cooldown in seconds, shortened by equipped items.
+    pub fn feed_cooldown_secs(&self, equipment: &ItemStats, config: &GameConfig) -> f64 {
+        (config.feed_delay * 3600) as f64 * (1.0 - equipment.cooldown_reduction)
+    }
+
+    pub fn feed_cooldown_remaining(&self, pig: &Pig, equipment: &ItemStats, config: &GameConfig) -> u64 {
+        let next_feed = pig.last_feed + self.feed_cooldown_secs(equipment, config);
+        (next_feed - unix_now()).max(0.0).ceil() as u64
+    }
+
+    pub fn format_cooldown_message(&self, pig_name: &str, remaining: u64) -> String {
+        format!(
+            "⏳ {} ещё не проголодался. Следующая кормёжка через {}",
+            pig_name,
+            format_duration(remaining)
+        )
+    }
+}
@@ -0,0 +1,309 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{Database, Duel, ItemStats, Pig, PigTraits};
+use crate::modules::callback::{CallbackData, answer_stale};
+use crate::modules::command::ParsedCommand;
+use super::buildings::Building;
+use super::events::GameEvent;
+use super::helpers::{duel_win_chance, unix_now};
+
+/// Duel power bonus per building level owned.
+const DUEL_BUILDING_BONUS: f64 = 0.01;
+/// Duel power per kilogram of equipped grow bonus.
+const DUEL_GEAR_POWER: f64 = 5.0;
+/// Finished duels listed by /duelstats.
+const RECENT_DUELS: i64 = 5;
+
+impl super::PigGameModule {
+    /// Fighting strength: weight, boosted by buildings, genes and gear, halved by poison.
+    pub fn duel_power(&self, pig: &Pig, equipment: &ItemStats, traits: &PigTraits) -> f64 {
+        let levels: i32 = Building::ALL.iter().map(|b| b.level(pig)).sum();
+        let mut power = pig.weight.max(1) as f64 * (1.0 + DUEL_BUILDING_BONUS * levels as f64 + traits.duel_strength)
+            + DUEL_GEAR_POWER * equipment.grow_bonus as f64;
+        if pig.poisoned {
+            power *= 0.5;
+        }
+        power
+    }
+
+    /// `/duel <wager> [name]` or in reply: challenge another pig for kilograms.
+    pub async fn handle_duel_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let Some(wager) = command.integer(0).and_then(|w| i32::try_from(w).ok()).filter(|w| *w > 0) else {
+            bot.send_message(msg.chat.id, "Использование: /duel <ставка в кг> в ответ на сообщение соперника или с именем его свиньи")
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let pigs = async {
+            let own = db.get_pig(chat_id, user_id).await?;
+            let target = self.resolve_target_pig(&msg, command.text(1).unwrap_or_default(), db).await?;
+            Ok::<_, sqlx::Error>((own, target))
+        };
+
+        let (own, target) = match pigs.await {
+            Ok((Some(own), Some(target))) => (own, target),
+            Ok((None, _)) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Ok((_, None)) => {
+                bot.send_message(msg.chat.id, "С кем драться? Ответь на сообщение соперника или укажи имя его свиньи")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if target.user_id == user_id {
+            bot.send_message(msg.chat.id, "Драться с самим собой? Иди подрочи лучше").reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        if wager >= own.weight || wager >= target.weight {
+            bot.send_message(
+                msg.chat.id,
+                format!("Ставка должна быть меньше веса обеих свиней ({} и {} кг)", own.weight, target.weight),
+            )
+            .reply_to(msg.id)
+            .await?;
+            return Ok(());
+        }
+
+        let expires_at = unix_now() + (config.game.duel_ttl * 60) as f64;
+        match db.create_duel(&own, &target, wager, expires_at).await {
+            Ok(duel) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "⚔️ {} ({} кг) вызывает {} ({} кг) на дуэль!\n💰 Ставка: {} кг\n{}, у тебя {} мин., чтобы ответить",
+                        own.name, own.weight, target.name, target.weight, wager, target.owner_name, config.game.duel_ttl
+                    ),
+                )
+                .reply_markup(self.create_duel_keyboard(duel.id))
+                .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to create duel: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `duel:<duel_id>:a` (target accepts) and `duel:<duel_id>:d` (either side declines).
+    pub async fn handle_duel_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let user_id = query.from.id.0 as i64;
+        let Some(duel_id) = data.get::<i32>(0) else {
+            return answer_stale(bot, query).await;
+        };
+        let accept = data.field(1) == Some("a");
+
+        let duel = match db.get_duel(duel_id).await {
+            Ok(Some(duel)) if duel.status == "pending" => duel,
+            Ok(_) => {
+                bot.answer_callback_query(&query.id).text("Дуэль уже закончилась").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        // Only the target may accept; either side may back out.
+        let allowed = user_id == duel.target_id || (user_id == duel.challenger_id && !accept);
+        if !allowed {
+            bot.answer_callback_query(&query.id).text("🖕🤣 Тебя на дуэль не звали!").await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let mut winner_id = None;
+        let (text, answer) = if duel.expires_at <= now {
+            db.close_duel(duel.id, "expired").await.ok();
+            ("⌛ Соперник струсил и не ответил. Дуэль отменена".to_string(), "Дуэль просрочена")
+        } else if !accept {
+            db.close_duel(duel.id, "declined").await.ok();
+            (format!("🏳️ {} отказался от дуэли", query.from.full_name()), "Дуэль отменена")
+        } else {
+            match self.fight_duel(&duel, now, db).await {
+                Ok(Some((text, winner))) => {
+                    winner_id = Some(winner);
+                    (text, "Дуэль состоялась")
+                }
+                Ok(None) => ("Дуэль уже закончилась".to_string(), "Дуэль уже закончилась"),
+                Err(e) => {
+                    log::error!("Failed to resolve duel: {}", e);
+                    bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                    return Ok(());
+                }
+            }
+        };
+
+        bot.edit_message_text(message.chat().id, message.id(), text).await?;
+        bot.answer_callback_query(&query.id).text(answer).await?;
+
+        if let Some(winner_id) = winner_id {
+            for participant in [duel.challenger_id, duel.target_id] {
+                let event = GameEvent::DuelFought { won: participant == winner_id };
+                self.emit_event(bot, message.chat().id, participant, event, db, config).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Rolls and settles an accepted duel between the pigs that were challenged,
+    /// returning the battle report and the winner.
+    async fn fight_duel(&self, duel: &Duel, now: f64, db: &Database) -> Result<Option<(String, i64)>, sqlx::Error> {
+        let pig = |pig_id: Option<i32>| async move {
+            match pig_id {
+                Some(pig_id) => db.get_pig_by_id(pig_id).await,
+                None => Ok(None),
+            }
+        };
+        let (Some(challenger), Some(target)) = (pig(duel.challenger_pig_id).await?, pig(duel.target_pig_id).await?) else {
+            db.close_duel(duel.id, "cancelled").await?;
+            return Ok(None);
+        };
+
+        let challenger_power = self.duel_power(
+            &challenger,
+            &ItemStats::total(&db.get_equipped_loot(duel.chat_id, duel.challenger_id).await?),
+            &db.get_pig_traits(challenger.id).await?,
+        );
+        let target_power = self.duel_power(
+            &target,
+            &ItemStats::total(&db.get_equipped_loot(duel.chat_id, duel.target_id).await?),
+            &db.get_pig_traits(target.id).await?,
+        );
+        let chance = duel_win_chance(challenger_power, target_power);
+
+        let (winner, loser) = if rand::random::<f64>() < chance {
+            (&challenger, &target)
+        } else {
+            (&target, &challenger)
+        };
+
+        let Some(settled) = db.resolve_duel(duel.id, winner, loser, now).await? else {
+            return Ok(None);
+        };
+
+        let report = format!(
+            "⚔️ {} против {}\n📊 Шансы: {:.0}% / {:.0}%\n\n🏆 Победил {}! Он отжал у {} {} кг",
+            challenger.name,
+            target.name,
+            chance * 100.0,
+            (1.0 - chance) * 100.0,
+            winner.name,
+            loser.name,
+            settled.transferred
+        );
+        Ok(Some((report, winner.user_id)))
+    }
+
+    /// `/duelstats [name]`: win/loss record and recent duels of a pig.
+    pub async fn handle_duel_stats_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let pig = if args.is_empty() && msg.reply_to_message().is_none() {
+            db.get_pig(chat_id, user_id).await
+        } else {
+            self.resolve_target_pig(&msg, &args.join(" "), db).await
+        };
+
+        let pig = match pig {
+            Ok(Some(pig)) => pig,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Свинья не найдена").reply_to(msg.id).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let history = async {
+            let stats = db.get_duel_stats(pig.id).await?;
+            let recent = db.get_recent_duels(pig.id, RECENT_DUELS).await?;
+            let mut lines = Vec::new();
+            for duel in recent {
+                let opponent_id = if duel.challenger_pig_id == Some(pig.id) { duel.target_pig_id } else { duel.challenger_pig_id };
+                let opponent = match opponent_id {
+                    Some(opponent_id) => db.get_pig_by_id(opponent_id).await?.map(|p| p.name),
+                    None => None,
+                }
+                .unwrap_or_else(|| "???".to_string());
+                let won = duel.winner_id == Some(pig.user_id);
+                lines.push(format!(
+                    "{} vs {}: {}{} кг",
+                    if won { "🏆" } else { "💀" },
+                    opponent,
+                    if won { "+" } else { "-" },
+                    duel.transferred
+                ));
+            }
+            Ok::<_, sqlx::Error>((stats, lines))
+        };
+
+        match history.await {
+            Ok((stats, lines)) => {
+                let mut text = format!(
+                    "⚔️ Дуэли {}\n🏆 Побед: {} (+{} кг)\n💀 Поражений: {} (-{} кг)",
+                    pig.name, stats.wins, stats.weight_won, stats.losses, stats.weight_lost
+                );
+                if !lines.is_empty() {
+                    text.push_str("\n\nПоследние бои:\n");
+                    text.push_str(&lines.join("\n"));
+                }
+                bot.send_message(msg.chat.id, text).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,43 @@
+use teloxide::prelude::*;
+use crate::config::Config;
+use crate::database::Database;
+use super::quests::QuestKind;
+
+/// Something a player did that quests and achievements react to.
+pub enum GameEvent {
+    /// `growth` is the weight actually gained, negative on a loss.
+    Fed { growth: i32 },
+    DuelFought { won: bool },
+    SaloCollected,
+    BuildingUpgraded,
+}
+
+impl GameEvent {
+    fn quest_steps(&self) -> Vec<(QuestKind, i32)> {
+        match *self {
+            GameEvent::Fed { growth } => vec![(QuestKind::Feed, 1), (QuestKind::GainWeight, growth)],
+            GameEvent::DuelFought { won } => vec![(QuestKind::WinDuel, won as i32)],
+            GameEvent::SaloCollected => vec![(QuestKind::CollectSalo, 1)],
+            GameEvent::BuildingUpgraded => Vec::new(),
+        }
+    }
+}
+
+impl super::PigGameModule {
+    /// Feeds a finished game event to quests and achievements. Failures are
+    /// only logged: bookkeeping must never break the event itself.
+    pub async fn emit_event(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user_id: i64,
+        event: GameEvent,
+        db: &Database,
+        config: &Config,
+    ) {
+        if let Err(e) = self.advance_quests(bot, chat_id, user_id, &event.quest_steps(), db, config).await {
+            log::error!("Failed to advance quests: {}", e);
+        }
+        self.announce_achievements(bot, chat_id, user_id, db).await;
+    }
+}
@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::config::Config;
+use crate::database::{Database, Pig};
+use crate::modules::permissions::{Role, deny, has_role};
+
+/// Pigs listed by /globaltop.
+const GLOBAL_TOP_SIZE: i64 = 10;
+
+impl super::PigGameModule {
+    /// `/globaltop` ranks pigs across chats; `/globaltop off|on` hides or shows this chat (admins and game masters only).
+    pub async fn handle_global_top_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        if let Some(&toggle) = args.first() {
+            let opt_out = match toggle {
+                "off" | "выкл" => true,
+                "on" | "вкл" => false,
+                _ => {
+                    bot.send_message(msg.chat.id, "Использование: /globaltop [on|off]").reply_to(msg.id).await?;
+                    return Ok(());
+                }
+            };
+
+            let Some(user) = msg.from.as_ref() else {
+                return Ok(());
+            };
+            let role = Role::Custom(super::GAME_MASTER);
+            if !has_role(&bot, db, config, msg.chat.id, user.id, role).await {
+                return deny(&bot, &msg, role).await;
+            }
+
+            let text = match db.set_global_opt_out(chat_id, opt_out).await {
+                Ok(()) if opt_out => "🔒 Свиньи этого чата больше не попадают в глобальный топ",
+                Ok(()) => "🌍 Свиньи этого чата снова участвуют в глобальном топе",
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных"
+                }
+            };
+            bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        let ranking = async {
+            let entries = db.get_global_leaderboard(GLOBAL_TOP_SIZE).await?;
+            let own = db.get_global_position(user_id).await?;
+            let settings = db.get_chat_settings(chat_id).await?;
+            Ok::<_, sqlx::Error>((entries, own, settings))
+        };
+
+        match ranking.await {
+            Ok((entries, _, _)) if entries.is_empty() => {
+                bot.send_message(msg.chat.id, "Во всём мире пока нет свиней 🐖").await?;
+            }
+            Ok((entries, own, settings)) => {
+                let mut lines = vec!["🌍 Глобальный топ свиней:".to_string()];
+                for (i, entry) in entries.iter().enumerate() {
+                    let medal = match i {
+                        0 => "🥇",
+                        1 => "🥈",
+                        2 => "🥉",
+                        _ => "🏅",
+                    };
+                    lines.push(format!(
+                        "{} {}. {} - {} кг (владелец: {})",
+                        medal,
+                        i + 1,
+                        entry.name,
+                        entry.score,
+                        entry.owner_name
+                    ));
+                }
+
+                lines.push(String::new());
+                match own {
+                    Some((position, entry)) => {
+                        lines.push(format!("👤 Твой лучший хряк {} на {} месте ({} кг)", entry.name, position, entry.score))
+                    }
+                    None => lines.push("👤 Твоих свиней в глобальном топе нет".to_string()),
+                }
+                if settings.global_opt_out {
+                    lines.push("🔒 Этот чат скрыт из глобального топа".to_string());
+                }
+
+                bot.send_message(msg.chat.id, lines.join("\n")).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `/profile`: the caller's pigs in every chat they play in, with totals.
+    pub async fn handle_profile_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let Some(user) = msg.from.as_ref() else {
+            return Ok(());
+        };
+        let user_id = user.id.0 as i64;
+
+        let pigs = match db.get_user_pigs(user_id).await {
+            Ok(pigs) if pigs.is_empty() => {
+                bot.send_message(msg.chat.id, "У вас нет ни одной свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Ok(pigs) => pigs,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let chats = self.profile_chat_titles(&bot, &msg, &pigs, db).await;
+        let mut lines = vec![format!("👤 Профиль {}", user.full_name()), String::new()];
+        for pig in &pigs {
+            let chat = chats.get(&pig.chat_id).map(String::as_str).unwrap_or("неизвестный чат");
+            lines.push(format!("🐷 {} — {} кг, {} 🥓 ({})", pig.name, pig.weight, pig.salo, chat));
+        }
+
+        let total_weight: i64 = pigs.iter().map(|p| p.weight as i64).sum();
+        let total_salo: i64 = pigs.iter().map(|p| p.salo as i64).sum();
+        lines.push(String::new());
+        lines.push(format!("🐖 Свиней: {}", pigs.len()));
+        lines.push(format!("⚖️ Общий вес: {} кг", total_weight));
+        lines.push(format!("🥓 Всего сала: {}", total_salo));
+
+        match db.get_global_position(user_id).await {
+            Ok(Some((position, _))) => lines.push(format!("🌍 Место в глобальном топе: {}", position)),
+            Ok(None) => {}
+            Err(e) => log::error!("Database error: {}", e),
+        }
+
+        bot.send_message(msg.chat.id, lines.join("\n")).await?;
+        Ok(())
+    }
+
+    /// Chat labels for /profile by chat id, with one Telegram lookup per chat.
+    /// Chats that opted out of the global top stay anonymous everywhere except
+    /// in themselves.
+    async fn profile_chat_titles(&self, bot: &Bot, msg: &Message, pigs: &[Pig], db: &Database) -> HashMap<i64, String> {
+        let mut chat_ids: Vec<i64> = pigs.iter().map(|pig| pig.chat_id).filter(|id| *id != msg.chat.id.0).collect();
+        chat_ids.sort_unstable();
+        chat_ids.dedup();
+
+        let hidden = db.get_opted_out_chats(&chat_ids).await.unwrap_or_else(|e| {
+            log::error!("Database error: {}", e);
+            chat_ids.clone()
+        });
+
+        let mut titles = HashMap::from([(msg.chat.id.0, "этот чат".to_string())]);
+        for chat_id in chat_ids {
+            let title = if hidden.contains(&chat_id) {
+                "🔒 скрытый чат".to_string()
+            } else {
+                match bot.get_chat(ChatId(chat_id)).await {
+                    Ok(chat) if chat.is_private() => "личка".to_string(),
+                    Ok(chat) => chat.title().unwrap_or("без названия").to_string(),
+                    Err(_) => "неизвестный чат".to_string(),
+                }
+            };
+            titles.insert(chat_id, title);
+        }
+        titles
+    }
+}
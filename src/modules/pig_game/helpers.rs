@@ -1,9 +1,85 @@
-use crate::database::{Database, Pig};
-use crate::config::GameConfig;
+use crate::database::{Database, ItemStats, Pig, PigTraits};
+use crate::config::{Config, GameConfig};
 use rand::prelude::*;
+use teloxide::types::Message;
+use super::buildings::Building;
+
+/// Salo produced per kilogram of pig weight in one `SALO_DELAY` period.
+const SALO_PER_KG: f64 = 0.1;
+/// Uncollected periods stop accumulating after this many.
+pub const MAX_SALO_PERIODS: u64 = 3;
+/// Vet clinic income stops accumulating after this many days.
+pub const MAX_VET_INCOME_DAYS: f64 = 3.0;
+
+/// Modifiers applied on top of the base grow range.
+pub struct GrowModifiers {
+    pub gain_multiplier: f64,
+    pub loss_multiplier: f64,
+    pub flat_bonus: i32,
+    /// Scales the whole gain, flat bonus included, for prestige points.
+    pub prestige_multiplier: f64,
+}
+
+impl Default for GrowModifiers {
+    fn default() -> Self {
+        Self {
+            gain_multiplier: 1.0,
+            loss_multiplier: 1.0,
+            flat_bonus: 0,
+            prestige_multiplier: 1.0,
+        }
+    }
+}
+
+pub fn unix_now() -> f64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs_f64()
+}
+
+/// Probability that a pig with `power` beats one with `opponent_power`,
+/// kept away from certainty so underdogs always have a shot.
+pub fn duel_win_chance(power: f64, opponent_power: f64) -> f64 {
+    let total = power + opponent_power;
+    if total <= 0.0 {
+        return 0.5;
+    }
+    (power / total).clamp(0.1, 0.9)
+}
+
+/// Rolls a uniformly distributed growth in `min..=max`.
+pub fn roll_growth(min: i32, max: i32) -> i32 {
+    if min >= max {
+        return min;
+    }
+    rand::rng().random_range(min..=max)
+}
+
+/// Formats a number of seconds as "2 ч 15 мин", rounding up to whole minutes.
+pub fn format_duration(secs: u64) -> String {
+    let total_minutes = secs.div_ceil(60);
+    let hours = total_minutes / 60;
+    let minutes = total_minutes % 60;
+
+    match (hours, minutes) {
+        (0, m) => format!("{} мин", m),
+        (h, 0) => format!("{} ч", h),
+        (h, m) => format!("{} ч {} мин", h, m),
+    }
+}
 
 impl super::PigGameModule {
-    pub fn calculate_grow_range(&self, score: f64, rank: i32, total_players: i32, config: &GameConfig) -> (i32, i32) {
+    pub fn grow_modifiers(&self, pig: &Pig, equipment: &ItemStats, traits: &PigTraits, prestige: i32, config: &Config) -> GrowModifiers {
+        GrowModifiers {
+            gain_multiplier: 1.0 + self.building_effect(Building::Barn, pig, config) + traits.growth_bias,
+            loss_multiplier: (1.0 - equipment.loss_reduction) * (1.0 - traits.loss_resistance),
+            flat_bonus: equipment.grow_bonus,
+            prestige_multiplier: 1.0 + config.game.prestige_bonus * prestige as f64,
+        }
+    }
+
+    pub fn calculate_grow_range(&self, score: f64, rank: i32, total_players: i32, modifiers: &GrowModifiers, config: &GameConfig) -> (i32, i32) {
         let loss_base = config.base_growth;
         let gain_base = config.weight_factor;
         let loss_coefficient = config.rank_factor;
@@ -14,14 +90,49 @@ impl super::PigGameModule {
         let loss_modifier = 1.0 - rank_percentage;
         let gain_modifier = 1.0 / (1.0 - rank_percentage + 1.0);
 
-        let max_loss = ((loss_base * score) * (loss_coefficient * loss_modifier)) + 15.0;
-        let max_gain = (gain_base * score) * (2.0 * gain_coefficient * gain_modifier) + 35.0;
+        let max_loss = (((loss_base * score) * (loss_coefficient * loss_modifier)) + 15.0) * modifiers.loss_multiplier;
+        let max_gain = (((gain_base * score) * (2.0 * gain_coefficient * gain_modifier) + 35.0) * modifiers.gain_multiplier
+            + modifiers.flat_bonus as f64)
+            * modifiers.prestige_multiplier;
 
         (-max_loss.floor() as i32, max_gain.floor() as i32)
     }
 
+    /// Returns the salo yield and the number of whole production periods it
+    /// covers for `elapsed` seconds since the last collection.
+    pub fn calculate_salo_yield(&self, elapsed: f64, period: f64, weight: i32, multiplier: f64) -> (i32, u64) {
+        if period <= 0.0 || elapsed < period {
+            return (0, 0);
+        }
+
+        let periods = ((elapsed / period).floor() as u64).min(MAX_SALO_PERIODS);
+        let per_period = weight.max(0) as f64 * SALO_PER_KG * multiplier;
+        let amount = (per_period * periods as f64).floor().max(1.0) as i32;
+
+        (amount, periods)
+    }
+
+    /// Salo earned by a vet clinic paying `daily_income` over `elapsed` seconds.
+    pub fn calculate_vet_income(&self, elapsed: f64, daily_income: f64) -> i32 {
+        let days = (elapsed.max(0.0) / 86400.0).min(MAX_VET_INCOME_DAYS);
+        (days * daily_income).floor() as i32
+    }
+
+    /// Finds the pig a command targets: the author of the replied-to message,
+    /// or else the first pig whose name matches `name`.
+    pub async fn resolve_target_pig(&self, msg: &Message, name: &str, db: &Database) -> Result<Option<Pig>, sqlx::Error> {
+        let chat_id = msg.chat.id.0;
+        if let Some(user) = msg.reply_to_message().and_then(|reply| reply.from.as_ref()) {
+            return db.get_pig(chat_id, user.id.0 as i64).await;
+        }
+        if name.is_empty() {
+            return Ok(None);
+        }
+        Ok(db.find_pig_by_name(chat_id, name).await?.into_iter().next())
+    }
+
     pub fn generate_default_pig_name(&self) -> String {
-        let names = vec![
+        let names = [
             "Хрякоблядь",
             "Свинопидор",
             "Ебаный Кабан",
@@ -36,7 +147,7 @@ impl super::PigGameModule {
 
         let mut rng = rand::rng();
         let random_index = rng.random_range(0..names.len());
-        format!("{}", names[random_index])
+        names[random_index].to_string()
     }
 
     pub async fn create_new_pig(
@@ -54,14 +165,14 @@ impl super::PigGameModule {
             weight: 0,
             name: pig_name.to_string(),
             last_feed: 0.0,
-            last_salo: 0.0,
+            last_salo: unix_now(),
             owner_name: owner_name.to_string(),
             salo: 0,
             poisoned: false,
             barn: 0,
             pigsty: 0,
             vetclinic: 0,
-            vet_last_pickup: 0.0,
+            vet_last_pickup: unix_now(),
             last_weight: 0,
             avatar_url: None,
             biolab: 0,
@@ -75,3 +186,105 @@ impl super::PigGameModule {
         db.create_pig(&new_pig).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::modules::pig_game::PigGameModule;
+
+    fn game_config() -> GameConfig {
+        GameConfig::default()
+    }
+
+    const PERIOD: f64 = 8.0 * 3600.0;
+
+    #[test]
+    fn salo_yield_is_zero_before_first_period() {
+        let module = PigGameModule::new();
+        assert_eq!(module.calculate_salo_yield(PERIOD - 1.0, PERIOD, 100, 1.0), (0, 0));
+    }
+
+    #[test]
+    fn salo_yield_scales_with_weight_and_periods() {
+        let module = PigGameModule::new();
+        assert_eq!(module.calculate_salo_yield(PERIOD, PERIOD, 100, 1.0), (10, 1));
+        assert_eq!(module.calculate_salo_yield(PERIOD * 2.5, PERIOD, 100, 1.0), (20, 2));
+    }
+
+    #[test]
+    fn salo_yield_caps_uncollected_periods() {
+        let module = PigGameModule::new();
+        let (amount, periods) = module.calculate_salo_yield(PERIOD * 100.0, PERIOD, 100, 1.0);
+        assert_eq!(periods, MAX_SALO_PERIODS);
+        assert_eq!(amount, 30);
+    }
+
+    #[test]
+    fn salo_yield_grows_with_multiplier() {
+        let module = PigGameModule::new();
+        assert_eq!(module.calculate_salo_yield(PERIOD, PERIOD, 100, 1.5), (15, 1));
+    }
+
+    #[test]
+    fn light_pigs_still_yield_some_salo() {
+        let module = PigGameModule::new();
+        assert_eq!(module.calculate_salo_yield(PERIOD, PERIOD, 1, 1.0), (1, 1));
+    }
+
+    #[test]
+    fn vet_income_accrues_daily_and_caps() {
+        let module = PigGameModule::new();
+        assert_eq!(module.calculate_vet_income(43200.0, 10.0), 5);
+        assert_eq!(module.calculate_vet_income(86400.0 * 30.0, 10.0), 30);
+        assert_eq!(module.calculate_vet_income(-5.0, 10.0), 0);
+    }
+
+    #[test]
+    fn duel_chance_favours_stronger_pig_within_bounds() {
+        assert_eq!(duel_win_chance(50.0, 50.0), 0.5);
+        assert!(duel_win_chance(80.0, 20.0) > 0.5);
+        assert_eq!(duel_win_chance(1000.0, 1.0), 0.9);
+        assert_eq!(duel_win_chance(1.0, 1000.0), 0.1);
+        assert_eq!(duel_win_chance(0.0, 0.0), 0.5);
+    }
+
+    #[test]
+    fn grow_range_spans_loss_and_gain() {
+        let module = PigGameModule::new();
+        let (min, max) = module.calculate_grow_range(100.0, 1, 10, &GrowModifiers::default(), &game_config());
+        assert!(min < 0);
+        assert!(max > 0);
+    }
+
+    #[test]
+    fn gain_multiplier_raises_grow_ceiling() {
+        let module = PigGameModule::new();
+        let config = game_config();
+        let (_, base_max) = module.calculate_grow_range(100.0, 1, 10, &GrowModifiers::default(), &config);
+        let boosted = GrowModifiers { gain_multiplier: 1.5, ..Default::default() };
+        let (_, boosted_max) = module.calculate_grow_range(100.0, 1, 10, &boosted, &config);
+        assert!(boosted_max > base_max);
+    }
+
+    #[test]
+    fn equipment_modifiers_shift_grow_range() {
+        let module = PigGameModule::new();
+        let config = game_config();
+        let (base_min, base_max) = module.calculate_grow_range(100.0, 5, 10, &GrowModifiers::default(), &config);
+        let geared = GrowModifiers { loss_multiplier: 0.5, flat_bonus: 10, ..Default::default() };
+        let (geared_min, geared_max) = module.calculate_grow_range(100.0, 5, 10, &geared, &config);
+        assert_eq!(geared_max, base_max + 10);
+        assert!(geared_min > base_min);
+    }
+
+    #[test]
+    fn prestige_scales_gain_but_not_loss() {
+        let module = PigGameModule::new();
+        let config = game_config();
+        let (base_min, base_max) = module.calculate_grow_range(0.0, 1, 1, &GrowModifiers::default(), &config);
+        let reborn = GrowModifiers { prestige_multiplier: 2.0, ..Default::default() };
+        let (reborn_min, reborn_max) = module.calculate_grow_range(0.0, 1, 1, &reborn, &config);
+        assert_eq!(reborn_max, base_max * 2);
+        assert_eq!(reborn_min, base_min);
+    }
+}
@@ -0,0 +1,149 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{InputFile, Message},
+};
+use crate::database::{Database, WeightRecord};
+use super::chart::render_line_chart;
+
+/// Feeds shown on the /history chart.
+const HISTORY_LIMIT: i64 = 100;
+const CHART_WIDTH: usize = 640;
+const CHART_HEIGHT: usize = 360;
+
+#[derive(Debug, PartialEq)]
+pub struct WeightSummary {
+    pub feeds: usize,
+    pub min_weight: i32,
+    pub max_weight: i32,
+    pub average_growth: f64,
+    pub best_growth: i32,
+    pub worst_growth: i32,
+}
+
+pub fn summarize_weight_history(records: &[WeightRecord]) -> Option<WeightSummary> {
+    if records.is_empty() {
+        return None;
+    }
+
+    let total_growth: i64 = records.iter().map(|r| r.growth as i64).sum();
+    Some(WeightSummary {
+        feeds: records.len(),
+        min_weight: records.iter().map(|r| r.weight).min()?,
+        max_weight: records.iter().map(|r| r.weight).max()?,
+        average_growth: total_growth as f64 / records.len() as f64,
+        best_growth: records.iter().map(|r| r.growth).max()?,
+        worst_growth: records.iter().map(|r| r.growth).min()?,
+    })
+}
+
+impl super::PigGameModule {
+    /// `/history [name]`: weight chart of a pig with a text summary as caption.
+    pub async fn handle_history_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let pig = if args.is_empty() && msg.reply_to_message().is_none() {
+            db.get_pig(chat_id, user_id).await
+        } else {
+            self.resolve_target_pig(&msg, &args.join(" "), db).await
+        };
+
+        let loaded = match pig {
+            Ok(Some(pig)) => db
+                .get_weight_history(pig.id, HISTORY_LIMIT)
+                .await
+                .map(|records| Some((pig, records))),
+            Ok(None) => Ok(None),
+            Err(e) => Err(e),
+        };
+
+        let (pig, records) = match loaded {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Свинья не найдена").reply_to(msg.id).await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let Some(summary) = summarize_weight_history(&records) else {
+            bot.send_message(msg.chat.id, format!("📈 {} ещё ни разу не ел, истории нет", pig.name))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let text = format!(
+            "📈 {} за последние {} кормёжек\n\
+             ⬇️ Минимум: {} кг\n\
+             ⬆️ Максимум: {} кг\n\
+             📊 Средний прирост: {:+.1} кг\n\
+             🚀 Лучший гров: {:+} кг\n\
+             💩 Худший гров: {:+} кг",
+            pig.name,
+            summary.feeds,
+            summary.min_weight,
+            summary.max_weight,
+            summary.average_growth,
+            summary.best_growth,
+            summary.worst_growth
+        );
+
+        if records.len() >= 2 {
+            let points: Vec<(f64, f64)> = records.iter().map(|r| (r.recorded_at, r.weight as f64)).collect();
+            match render_line_chart(&points, CHART_WIDTH, CHART_HEIGHT) {
+                Ok(png) => {
+                    let sent = bot
+                        .send_photo(msg.chat.id, InputFile::memory(png).file_name("history.png"))
+                        .caption(text.clone())
+                        .await;
+                    match sent {
+                        Ok(_) => return Ok(()),
+                        Err(e) => log::error!("Failed to send history chart: {}", e),
+                    }
+                }
+                Err(e) => log::error!("Failed to render history chart: {}", e),
+            }
+        }
+
+        bot.send_message(msg.chat.id, text).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(weight: i32, growth: i32) -> WeightRecord {
+        WeightRecord { weight, growth, recorded_at: 0.0 }
+    }
+
+    #[test]
+    fn summary_of_empty_history_is_none() {
+        assert_eq!(summarize_weight_history(&[]), None);
+    }
+
+    #[test]
+    fn summary_tracks_extremes_and_average() {
+        let records = [record(10, 10), record(30, 20), record(18, -12)];
+        let summary = summarize_weight_history(&records).unwrap();
+        assert_eq!(summary.feeds, 3);
+        assert_eq!(summary.min_weight, 10);
+        assert_eq!(summary.max_weight, 30);
+        assert_eq!(summary.best_growth, 20);
+        assert_eq!(summary.worst_growth, -12);
+        assert!((summary.average_growth - 6.0).abs() < 1e-9);
+    }
+}
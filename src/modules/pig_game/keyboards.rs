@@ -1,13 +1,24 @@
-use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup, MessageId};
+use teloxide::types::{InlineKeyboardButton, InlineKeyboardMarkup};
+use crate::config::Config;
+use crate::modules::callback::CallbackData;
+use crate::database::{LeaderboardSort, Loot, MarketListing, Pig, StablePig};
+use super::buildings::Building;
+use super::helpers::format_duration;
 
 impl super::PigGameModule {
-    pub fn create_pig_keyboard(&self, user_id: i64, command_message_id: i32) -> InlineKeyboardMarkup {
+    pub fn create_pig_keyboard(&self, user_id: i64, command_message_id: i32, cooldown_remaining: u64) -> InlineKeyboardMarkup {
+        let grow_text = if cooldown_remaining > 0 {
+            format!("⏳ Гров через {}", format_duration(cooldown_remaining))
+        } else {
+            "🐷 ГРОВИМ!".to_string()
+        };
+
         InlineKeyboardMarkup::new(vec![
             vec![
-                InlineKeyboardButton::callback("🐷 ГРОВИМ!", &format!("grow:{}", user_id)),
+                CallbackData::new("grow").with(user_id).button(grow_text),
             ],
             vec![
-                InlineKeyboardButton::callback("🗑 Удалить", &format!("remove:{}", command_message_id)),
+                CallbackData::new("remove").with(command_message_id).button("🗑 Удалить"),
             ],
         ])
 
@@ -16,8 +27,180 @@ impl super::PigGameModule {
     pub fn create_grow_keyboard(&self, user_id: i64) -> InlineKeyboardMarkup {
         InlineKeyboardMarkup::new(vec![
             vec![
-                InlineKeyboardButton::callback("🔙 Назад", &format!("back:{}", user_id)),
+                CallbackData::new("back").with(user_id).button("🔙 Назад"),
             ]
         ])
     }
+
+    pub fn create_build_keyboard(&self, pig: &Pig, config: &Config) -> InlineKeyboardMarkup {
+        let rows = Building::ALL
+            .iter()
+            .map(|&building| {
+                let building_config = building.config(&config.buildings);
+                let level = building.level(pig);
+                let text = if level >= building_config.max_level {
+                    format!("{} {} — макс.", building_config.icon, building_config.name)
+                } else {
+                    format!(
+                        "{} {} → {} ({} 🥓)",
+                        building_config.icon,
+                        building_config.name,
+                        level + 1,
+                        self.building_upgrade_cost(building_config, level)
+                    )
+                };
+                vec![CallbackData::new("build").with(pig.user_id).with(building.key()).button(text)]
+            })
+            .collect::<Vec<_>>();
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_inventory_keyboard(&self, user_id: i64, page_items: &[&Loot], page: usize, pages: usize) -> InlineKeyboardMarkup {
+        let mut rows: Vec<Vec<InlineKeyboardButton>> = page_items
+            .iter()
+            .map(|item| {
+                let text = if item.equipped {
+                    format!("❌ Снять {} {}", item.icon, item.name)
+                } else {
+                    format!("🎽 Надеть {} {}", item.icon, item.name)
+                };
+                vec![CallbackData::new("eq").with(user_id).with(item.id).with(page).button(text)]
+            })
+            .collect();
+
+        let mut row = Vec::new();
+        if page > 0 {
+            row.push(CallbackData::new("inv").with(user_id).with(page - 1).button("◀️"));
+        }
+        if page + 1 < pages {
+            row.push(CallbackData::new("inv").with(user_id).with(page + 1).button("▶️"));
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_top_keyboard(&self, user_id: i64, sort: LeaderboardSort, page: usize, pages: usize) -> InlineKeyboardMarkup {
+        let sorts = LeaderboardSort::ALL
+            .iter()
+            .map(|&option| {
+                let title = self.leaderboard_title(option);
+                let text = if option == sort { format!("• {}", title) } else { title.to_string() };
+                CallbackData::new("top").with(user_id).with(option.key()).with(0).button(text)
+            })
+            .collect::<Vec<_>>();
+        let mut rows: Vec<Vec<InlineKeyboardButton>> = sorts.chunks(2).map(|chunk| chunk.to_vec()).collect();
+
+        let mut row = Vec::new();
+        if page > 0 {
+            row.push(CallbackData::new("top").with(user_id).with(sort.key()).with(page - 1).button("◀️"));
+        }
+        if page + 1 < pages {
+            row.push(CallbackData::new("top").with(user_id).with(sort.key()).with(page + 1).button("▶️"));
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_hall_of_fame_keyboard(&self, page: usize, pages: usize) -> InlineKeyboardMarkup {
+        let mut row = Vec::new();
+        if page > 0 {
+            row.push(CallbackData::new("hof").with(page - 1).button("◀️"));
+        }
+        if page + 1 < pages {
+            row.push(CallbackData::new("hof").with(page + 1).button("▶️"));
+        }
+
+        let mut rows = Vec::new();
+        if !row.is_empty() {
+            rows.push(row);
+        }
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_quests_keyboard(&self, user_id: i64) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![
+            CallbackData::new("quests").with(user_id).button("🔄 Обновить"),
+        ]])
+    }
+
+    pub fn create_breed_keyboard(&self, initiator_id: i64, partner_id: i64, expires_at: i64) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![
+            CallbackData::new("breed").with(initiator_id).with(partner_id).with(expires_at).with("a").button("💞 Согласен"),
+            CallbackData::new("breed").with(initiator_id).with(partner_id).with(expires_at).with("d").button("💔 Отказаться"),
+        ]])
+    }
+
+    pub fn create_select_keyboard(&self, user_id: i64, pigs: &[StablePig]) -> InlineKeyboardMarkup {
+        let rows = pigs
+            .iter()
+            .map(|stable_pig| {
+                let mark = if stable_pig.active { "✅" } else { "🐖" };
+                vec![
+                    CallbackData::new("select")
+                        .with(user_id)
+                        .with(stable_pig.pig.id)
+                        .button(format!("{} {} ({} кг)", mark, stable_pig.pig.name, stable_pig.pig.weight)),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_slaughter_keyboard(&self, user_id: i64, pig_id: i32) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![
+            CallbackData::new("slaughter").with(user_id).with(pig_id).with("a").button("🔪 Забить"),
+            CallbackData::new("slaughter").with(user_id).with(pig_id).with("d").button("🐷 Помиловать"),
+        ]])
+    }
+
+    /// One button per listing on the page; pressing your own lot withdraws it.
+    pub fn create_market_keyboard(&self, listings: &[MarketListing], page: usize, pages: usize) -> InlineKeyboardMarkup {
+        let mut rows: Vec<Vec<InlineKeyboardButton>> = listings
+            .iter()
+            .map(|listing| {
+                vec![
+                    CallbackData::new("market")
+                        .with("buy")
+                        .with(listing.id)
+                        .with(page)
+                        .button(format!("{} {} — {} 🥓", listing.item_icon, listing.item_name, listing.price)),
+                ]
+            })
+            .collect();
+
+        let mut row = Vec::new();
+        if page > 0 {
+            row.push(CallbackData::new("market").with("page").with(page - 1).button("◀️"));
+        }
+        if page + 1 < pages {
+            row.push(CallbackData::new("market").with("page").with(page + 1).button("▶️"));
+        }
+        if !row.is_empty() {
+            rows.push(row);
+        }
+
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    pub fn create_offer_keyboard(&self, offer_id: i32) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![
+            CallbackData::new("trade").with(offer_id).with("a").button("✅ Подтвердить"),
+            CallbackData::new("trade").with(offer_id).with("d").button("❌ Отказаться"),
+        ]])
+    }
+
+    pub fn create_duel_keyboard(&self, duel_id: i32) -> InlineKeyboardMarkup {
+        InlineKeyboardMarkup::new(vec![vec![
+            CallbackData::new("duel").with(duel_id).with("a").button("⚔️ Принять бой"),
+            CallbackData::new("duel").with(duel_id).with("d").button("🏳️ Отказаться"),
+        ]])
+    }
 }
@@ -0,0 +1,383 @@
+use std::sync::LazyLock;
+use rand::prelude::*;
+use serde::Deserialize;
+use sqlx::types::Json;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, InlineKeyboardMarkup, MaybeInaccessibleMessage, Message},
+};
+use uuid::Uuid;
+use crate::config::Config;
+use crate::database::{Database, ItemStats, Loot, Pig, Rarity};
+use crate::modules::callback::{CallbackData, answer_stale};
+
+/// Items shown on one inventory page.
+pub const INVENTORY_PAGE_SIZE: usize = 5;
+/// Caps on combined equipment stats, so stacking gear never removes losses
+/// or the cooldown entirely.
+const MAX_LOSS_REDUCTION: f64 = 0.75;
+const MAX_COOLDOWN_REDUCTION: f64 = 0.5;
+
+#[derive(Debug, Deserialize)]
+pub struct RarityTemplate {
+    pub rarity: Rarity,
+    pub name: String,
+    pub icon: String,
+    pub weight: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ItemTemplate {
+    pub name: String,
+    pub icon: String,
+    pub description: Option<String>,
+    pub class_name: String,
+    pub class_icon: String,
+    pub weight: f64,
+    pub rarity: Rarity,
+    #[serde(default)]
+    pub stats: ItemStats,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LootCatalogue {
+    pub rarities: Vec<RarityTemplate>,
+    pub items: Vec<ItemTemplate>,
+}
+
+static CATALOGUE: LazyLock<LootCatalogue> = LazyLock::new(|| {
+    serde_yaml::from_str(include_str!("loot.yaml")).expect("Invalid loot catalogue")
+});
+
+impl LootCatalogue {
+    pub fn get() -> &'static LootCatalogue {
+        &CATALOGUE
+    }
+
+    pub fn rarity(&self, rarity: Rarity) -> Option<&RarityTemplate> {
+        self.rarities.iter().find(|r| r.rarity == rarity)
+    }
+
+    /// Picks a rarity by weight, then a uniformly random item of that rarity.
+    pub fn roll(&self, rng: &mut impl Rng) -> Option<&ItemTemplate> {
+        let available: Vec<&RarityTemplate> = self
+            .rarities
+            .iter()
+            .filter(|r| self.items.iter().any(|item| item.rarity == r.rarity))
+            .collect();
+        let rarity = available.choose_weighted(rng, |r| r.weight).ok()?.rarity;
+
+        let items: Vec<&ItemTemplate> = self.items.iter().filter(|item| item.rarity == rarity).collect();
+        items.choose(rng).copied()
+    }
+}
+
+impl ItemTemplate {
+    pub fn to_loot(&self, chat_id: i64, owner: i64) -> Loot {
+        Loot {
+            id: 0,
+            chat_id,
+            owner,
+            name: self.name.clone(),
+            icon: self.icon.clone(),
+            description: self.description.clone(),
+            class_name: self.class_name.clone(),
+            class_icon: self.class_icon.clone(),
+            weight: self.weight,
+            base_stats: Json(self.stats.clone()),
+            rarity: Json(self.rarity),
+            uuid: Uuid::new_v4(),
+            equipped: false,
+        }
+    }
+}
+
+impl ItemStats {
+    /// Combined stats of equipped items, with reductions capped.
+    pub fn total(items: &[Loot]) -> ItemStats {
+        let mut total = items.iter().fold(ItemStats::default(), |acc, item| ItemStats {
+            grow_bonus: acc.grow_bonus + item.base_stats.grow_bonus,
+            loss_reduction: acc.loss_reduction + item.base_stats.loss_reduction,
+            cooldown_reduction: acc.cooldown_reduction + item.base_stats.cooldown_reduction,
+        });
+        total.loss_reduction = total.loss_reduction.clamp(0.0, MAX_LOSS_REDUCTION);
+        total.cooldown_reduction = total.cooldown_reduction.clamp(0.0, MAX_COOLDOWN_REDUCTION);
+        total
+    }
+}
+
+impl super::PigGameModule {
+    pub fn format_rarity(&self, rarity: Rarity) -> String {
+        match LootCatalogue::get().rarity(rarity) {
+            Some(template) => format!("{} {}", template.icon, template.name),
+            None => format!("{:?}", rarity),
+        }
+    }
+
+    pub fn format_item_stats(&self, stats: &ItemStats) -> String {
+        let mut parts = Vec::new();
+        if stats.grow_bonus != 0 {
+            parts.push(format!("+{} кг к приросту", stats.grow_bonus));
+        }
+        if stats.loss_reduction != 0.0 {
+            parts.push(format!("-{}% к потерям", (stats.loss_reduction * 100.0).round()));
+        }
+        if stats.cooldown_reduction != 0.0 {
+            parts.push(format!("-{}% к ожиданию", (stats.cooldown_reduction * 100.0).round()));
+        }
+        if parts.is_empty() {
+            "без эффектов".to_string()
+        } else {
+            parts.join(", ")
+        }
+    }
+
+    pub fn format_item(&self, item: &Loot) -> String {
+        format!(
+            "{}{} {} ({})\n    {} {} · {}",
+            if item.equipped { "🎽 " } else { "" },
+            item.icon,
+            item.name,
+            self.format_rarity(item.rarity.0),
+            item.class_icon,
+            item.class_name,
+            self.format_item_stats(&item.base_stats)
+        )
+    }
+
+    /// Combined stats of the pig owner's equipped items. Errors are logged and
+    /// treated as no equipment, for display paths that cannot fail.
+    pub async fn equipment_stats(&self, pig: &Pig, db: &Database) -> ItemStats {
+        match db.get_equipped_loot(pig.chat_id, pig.user_id).await {
+            Ok(items) => ItemStats::total(&items),
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                ItemStats::default()
+            }
+        }
+    }
+
+    /// Rolls a drop for a fed pig and stores it if the owner has room.
+    /// Returns a line for the feed report, if anything happened.
+    pub async fn roll_loot_drop(&self, pig: &Pig, db: &Database, config: &Config) -> Result<Option<String>, sqlx::Error> {
+        let template = {
+            let mut rng = rand::rng();
+            if rng.random::<f64>() >= config.game.loot_chance {
+                return Ok(None);
+            }
+            match LootCatalogue::get().roll(&mut rng) {
+                Some(template) => template,
+                None => return Ok(None),
+            }
+        };
+
+        let loot = template.to_loot(pig.chat_id, pig.user_id);
+        let note = match db.add_loot(&loot, self.max_items(pig, config)).await? {
+            Some(item) => format!("🎁 Найден предмет: {} {} ({})", item.icon, item.name, self.format_rarity(item.rarity.0)),
+            None => format!("🎁 Свинья нашла {} {}, но инвентарь полон. Улучшите склад в /build", loot.icon, loot.name),
+        };
+
+        Ok(Some(note))
+    }
+
+    pub fn format_inventory_page(&self, owner_name: &str, items: &[Loot], page: usize, max_items: i64) -> String {
+        if items.is_empty() {
+            return format!("🎒 Инвентарь {} пуст (0/{})", owner_name, max_items);
+        }
+
+        let start = page * INVENTORY_PAGE_SIZE;
+        let lines: Vec<String> = items
+            .iter()
+            .enumerate()
+            .skip(start)
+            .take(INVENTORY_PAGE_SIZE)
+            .map(|(i, item)| format!("{}. {}", i + 1, self.format_item(item)))
+            .collect();
+
+        format!(
+            "🎒 Инвентарь {} ({}/{})\n\n{}",
+            owner_name,
+            items.len(),
+            max_items,
+            lines.join("\n")
+        )
+    }
+
+    pub fn inventory_page_count(&self, items: usize) -> usize {
+        items.div_ceil(INVENTORY_PAGE_SIZE).max(1)
+    }
+
+    /// Loads one inventory page with its keyboard. `None` if the user has no pig.
+    async fn render_inventory(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        page: usize,
+        db: &Database,
+        config: &Config,
+    ) -> Result<Option<(String, InlineKeyboardMarkup)>, sqlx::Error> {
+        let Some(pig) = db.get_pig(chat_id, user_id).await? else {
+            return Ok(None);
+        };
+        let items = db.get_user_loot(chat_id, user_id).await?;
+
+        let pages = self.inventory_page_count(items.len());
+        let page = page.min(pages - 1);
+        let text = self.format_inventory_page(&pig.owner_name, &items, page, self.max_items(&pig, config));
+        let page_items: Vec<&Loot> = items.iter().skip(page * INVENTORY_PAGE_SIZE).take(INVENTORY_PAGE_SIZE).collect();
+        let keyboard = self.create_inventory_keyboard(user_id, &page_items, page, pages);
+
+        Ok(Some((text, keyboard)))
+    }
+
+    pub async fn handle_inventory_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        match self.render_inventory(chat_id, user_id, 0, db, config).await {
+            Ok(Some((text, keyboard))) => {
+                bot.send_message(msg.chat.id, text)
+                    .reply_markup(keyboard)
+                    .await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `inv:<user_id>:<page>` pagination and
+    /// `eq:<user_id>:<loot_id>:<page>` equip toggles.
+    pub async fn handle_inventory_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let Some(original_user_id) = data.get::<i64>(0) else {
+            return answer_stale(bot, query).await;
+        };
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id)
+                .text("🖕🤣 Не лезь в чужой мешок!")
+                .await?;
+            return Ok(());
+        }
+
+        let (page, notice) = if data.namespace() == "eq" {
+            let (Some(loot_id), Some(page)) = (data.get::<i32>(1), data.get::<usize>(2)) else {
+                return answer_stale(bot, query).await;
+            };
+            (page, self.toggle_equipped(chat_id, user_id, loot_id, db).await)
+        } else {
+            let Some(page) = data.get::<usize>(1) else {
+                return answer_stale(bot, query).await;
+            };
+            (page, Ok(None))
+        };
+
+        let rendered = match notice {
+            Ok(notice) => self.render_inventory(chat_id, user_id, page, db, config).await.map(|r| (r, notice)),
+            Err(e) => Err(e),
+        };
+
+        match rendered {
+            Ok((Some((text, keyboard)), notice)) => {
+                bot.edit_message_text(message.chat().id, message.id(), text)
+                    .reply_markup(keyboard)
+                    .await?;
+                let answer = bot.answer_callback_query(&query.id);
+                match notice {
+                    Some(notice) => answer.text(notice).await?,
+                    None => answer.await?,
+                };
+            }
+            Ok((None, _)) => {
+                bot.answer_callback_query(&query.id)
+                    .text("У вас нет свиньи!")
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id)
+                    .text("Ошибка базы данных")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn toggle_equipped(&self, chat_id: i64, user_id: i64, loot_id: i32, db: &Database) -> Result<Option<String>, sqlx::Error> {
+        let items = db.get_user_loot(chat_id, user_id).await?;
+        let Some(item) = items.iter().find(|item| item.id == loot_id) else {
+            return Ok(Some("Этого предмета у вас больше нет".to_string()));
+        };
+
+        let equip = !item.equipped;
+        db.set_loot_equipped(chat_id, user_id, loot_id, equip).await?;
+
+        Ok(Some(if equip {
+            format!("🎽 {} {} надет", item.icon, item.name)
+        } else {
+            format!("{} {} снят", item.icon, item.name)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn catalogue_parses_and_every_rarity_is_known() {
+        let catalogue = LootCatalogue::get();
+        assert!(!catalogue.items.is_empty());
+        for item in &catalogue.items {
+            assert!(catalogue.rarity(item.rarity).is_some(), "{} has an unknown rarity", item.name);
+        }
+    }
+
+    #[test]
+    fn total_stats_sum_and_cap_reductions() {
+        let catalogue = LootCatalogue::get();
+        let crown = catalogue.items.iter().find(|item| item.rarity == Rarity::Legendary).unwrap();
+        let items: Vec<Loot> = (0..5).map(|_| crown.to_loot(1, 1)).collect();
+
+        let total = ItemStats::total(&items);
+        assert_eq!(total.grow_bonus, crown.stats.grow_bonus * 5);
+        assert_eq!(total.loss_reduction, MAX_LOSS_REDUCTION);
+        assert_eq!(total.cooldown_reduction, MAX_COOLDOWN_REDUCTION);
+    }
+
+    #[test]
+    fn roll_always_returns_an_item() {
+        let catalogue = LootCatalogue::get();
+        let mut rng = rand::rng();
+        for _ in 0..100 {
+            assert!(catalogue.roll(&mut rng).is_some());
+        }
+    }
+}
@@ -0,0 +1,352 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, InlineKeyboardMarkup, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{Database, MarketFailure, MarketListing};
+use crate::modules::callback::{CallbackData, answer_stale};
+use crate::modules::command::ParsedCommand;
+use super::helpers::{format_duration, unix_now};
+
+/// Listings per /market page.
+const MARKET_PAGE_SIZE: usize = 5;
+/// Sales listed by /market history.
+const MARKET_HISTORY_SIZE: i64 = 15;
+/// Keeps prices well inside the salo column.
+const MAX_PRICE: i64 = 1_000_000;
+
+fn format_date(timestamp: f64) -> String {
+    chrono::DateTime::from_timestamp(timestamp as i64, 0)
+        .map(|date| date.format("%d.%m %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+impl super::PigGameModule {
+    /// `/sell <n> <price>`: puts item `n` of the player's `/inventory` on the market.
+    pub async fn handle_sell_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let Some(user) = msg.from.as_ref() else {
+            return Ok(());
+        };
+        let user_id = user.id.0 as i64;
+
+        let number = command.integer(0).and_then(|n| usize::try_from(n).ok()).filter(|n| *n > 0);
+        let price = command.integer(1).filter(|p| (1..=MAX_PRICE).contains(p));
+        let (Some(number), Some(price)) = (number, price) else {
+            bot.send_message(msg.chat.id, format!("Номер предмета и цена должны быть от 1, цена не больше {} 🥓", MAX_PRICE))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let loaded = async {
+            let has_pig = db.get_pig(chat_id, user_id).await?.is_some();
+            let items = db.get_user_loot(chat_id, user_id).await?;
+            Ok::<_, sqlx::Error>((has_pig, items))
+        };
+
+        let items = match loaded.await {
+            Ok((false, _)) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Выручку некуда класть. Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Ok((true, items)) => items,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let Some(item) = items.get(number - 1) else {
+            bot.send_message(msg.chat.id, format!("У вас нет предмета №{}. Загляните в /inventory", number))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let now = unix_now();
+        let ttl = config.game.market_listing_ttl * 3600;
+        match db.create_listing(item, &user.full_name(), price as i32, now, now + ttl as f64).await {
+            Ok(Some(listing)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "🏪 {} выставил на рынок {} {} за {} 🥓\nЛот провисит {}. Купить: /market",
+                        listing.seller_name,
+                        listing.item_icon,
+                        listing.item_name,
+                        listing.price,
+                        format_duration(ttl)
+                    ),
+                )
+                .await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Этот предмет уже лежит на рынке")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to create market listing: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `/market` browses the chat's listings; `/market history` shows recent sales.
+    pub async fn handle_market_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+
+        match command.text(0) {
+            None => {}
+            Some("history" | "история") => return self.send_market_history(bot, msg, db).await,
+            Some(_) => {
+                bot.send_message(msg.chat.id, "Использование: /market [history]").reply_to(msg.id).await?;
+                return Ok(());
+            }
+        }
+
+        match self.render_market(chat_id, 0, db, config).await {
+            Ok((text, keyboard)) => {
+                bot.send_message(msg.chat.id, text).reply_markup(keyboard).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_market_history(&self, bot: Bot, msg: Message, db: &Database) -> ResponseResult<()> {
+        match db.get_market_sales(msg.chat.id.0, MARKET_HISTORY_SIZE).await {
+            Ok(sales) if sales.is_empty() => {
+                bot.send_message(msg.chat.id, "📜 На рынке ещё ничего не продали").await?;
+            }
+            Ok(sales) => {
+                let mut lines = vec!["📜 Последние сделки:".to_string()];
+                for sale in &sales {
+                    lines.push(format!(
+                        "{} {} — {} 🥓: {} → {} · {}",
+                        sale.item_icon,
+                        sale.item_name,
+                        sale.price,
+                        sale.seller_name,
+                        sale.buyer_name.as_deref().unwrap_or("?"),
+                        sale.sold_at.map(format_date).unwrap_or_default()
+                    ));
+                }
+                bot.send_message(msg.chat.id, lines.join("\n")).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Loads one market page with its keyboard.
+    async fn render_market(
+        &self,
+        chat_id: i64,
+        page: usize,
+        db: &Database,
+        config: &Config,
+    ) -> Result<(String, InlineKeyboardMarkup), sqlx::Error> {
+        let now = unix_now();
+        let total = db.count_market_listings(chat_id, now).await? as usize;
+        let pages = total.div_ceil(MARKET_PAGE_SIZE).max(1);
+        let page = page.min(pages - 1);
+        let listings = db
+            .get_market_listings(chat_id, now, MARKET_PAGE_SIZE as i64, (page * MARKET_PAGE_SIZE) as i64)
+            .await?;
+
+        let text = self.format_market_page(&listings, now, page, pages, config);
+        Ok((text, self.create_market_keyboard(&listings, page, pages)))
+    }
+
+    fn format_market_page(&self, listings: &[MarketListing], now: f64, page: usize, pages: usize, config: &Config) -> String {
+        if listings.is_empty() {
+            return "🏪 На рынке пусто. Выставь что-нибудь: /sell <номер> <цена>".to_string();
+        }
+
+        let mut lines = vec![format!("🏪 Рынок (стр. {}/{})", page + 1, pages), String::new()];
+        for listing in listings {
+            lines.push(format!(
+                "{} {} — {} 🥓 · {}, ещё {}",
+                listing.item_icon,
+                listing.item_name,
+                listing.price,
+                listing.seller_name,
+                format_duration((listing.expires_at - now).max(0.0) as u64)
+            ));
+        }
+        lines.push(format!(
+            "\nЖми на лот, чтобы купить, свой лот так же снимается с продажи. Рынок забирает {:.0}% с каждой сделки",
+            config.game.market_fee * 100.0
+        ));
+        lines.join("\n")
+    }
+
+    /// Handles `market:page:<n>` and `market:buy:<listing_id>:<page>` presses.
+    pub async fn handle_market_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+
+        let page = match data.field(0) {
+            Some("buy") => {
+                let (Some(listing_id), Some(page)) = (data.get::<i32>(1), data.get::<usize>(2)) else {
+                    return answer_stale(bot, query).await;
+                };
+                if !self.press_listing(bot, query, message, listing_id, db, config).await? {
+                    return Ok(());
+                }
+                page
+            }
+            Some("page") => {
+                let Some(page) = data.get::<usize>(1) else {
+                    return answer_stale(bot, query).await;
+                };
+                bot.answer_callback_query(&query.id).await?;
+                page
+            }
+            _ => return answer_stale(bot, query).await,
+        };
+
+        match self.render_market(chat_id, page, db, config).await {
+            Ok((text, keyboard)) => {
+                // The page may not have changed if someone else refreshed it first.
+                if let Err(e) = bot
+                    .edit_message_text(message.chat().id, message.id(), text)
+                    .reply_markup(keyboard)
+                    .await
+                {
+                    log::warn!("Failed to refresh market page: {}", e);
+                }
+            }
+            Err(e) => log::error!("Database error: {}", e),
+        }
+
+        Ok(())
+    }
+
+    /// Buys the listing, or withdraws it if the presser is its seller. Answers
+    /// the query and returns whether the market page changed.
+    async fn press_listing(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        listing_id: i32,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<bool> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        match db.withdraw_listing(listing_id, user_id).await {
+            Ok(Some(listing)) => {
+                bot.answer_callback_query(&query.id)
+                    .text(format!("{} {} снят с продажи", listing.item_icon, listing.item_name))
+                    .await?;
+                return Ok(true);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                return Ok(false);
+            }
+        }
+
+        let bought = async {
+            let Some(pig) = db.get_pig(chat_id, user_id).await? else {
+                return Ok(None);
+            };
+            let sale = db
+                .buy_listing(
+                    listing_id,
+                    user_id,
+                    &query.from.full_name(),
+                    self.max_items(&pig, config),
+                    config.game.market_fee,
+                    unix_now(),
+                )
+                .await?;
+            Ok::<_, sqlx::Error>(Some(sale))
+        };
+
+        let failure = match bought.await {
+            Ok(Some(Ok(sale))) => {
+                bot.answer_callback_query(&query.id)
+                    .text(format!("Куплено: {} {}", sale.item_icon, sale.item_name))
+                    .await?;
+                bot.send_message(
+                    message.chat().id,
+                    format!(
+                        "🤝 {} купил {} {} у {} за {} 🥓 (рынку ушло {} 🥓)",
+                        sale.buyer_name.as_deref().unwrap_or("?"),
+                        sale.item_icon,
+                        sale.item_name,
+                        sale.seller_name,
+                        sale.price,
+                        sale.fee
+                    ),
+                )
+                .await?;
+                return Ok(true);
+            }
+            Ok(Some(Err(failure))) => failure,
+            Ok(None) => {
+                bot.answer_callback_query(&query.id)
+                    .text("Без свиньи на рынок не пускают. Создай её командой /pig <имя>")
+                    .await?;
+                return Ok(false);
+            }
+            Err(e) => {
+                log::error!("Failed to buy market listing: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                return Ok(false);
+            }
+        };
+
+        let text = match failure {
+            MarketFailure::Closed => "Этот лот уже продан или снят",
+            MarketFailure::ItemMissing => "Продавец куда-то дел этот предмет, лот снят",
+            MarketFailure::NotEnoughSalo => "Не хватает сала, нищеброд",
+            MarketFailure::InventoryFull => "Инвентарь забит, сначала освободи место",
+        };
+        bot.answer_callback_query(&query.id).text(text).await?;
+        Ok(matches!(failure, MarketFailure::Closed | MarketFailure::ItemMissing))
+    }
+}
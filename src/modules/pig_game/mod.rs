@@ -1,4 +1,6 @@
 use crate::modules::BotModule;
+use crate::modules::callback::CallbackData;
+use crate::modules::command::{ArgKind, CommandScope, CommandSpec, ParsedCommand};
 use crate::config::Config;
 use crate::database::Database;
 use async_trait::async_trait;
@@ -7,10 +9,40 @@ use teloxide::{
     types::{Message, CallbackQuery}
 };
 
+mod achievements;
+mod breeding;
+mod buildings;
 mod callbacks;
+mod chart;
 mod commands;
+mod cooldown;
+mod duel;
+mod events;
+mod global;
 mod keyboards;
 mod helpers;
+mod history;
+mod loot;
+mod market;
+mod pills;
+mod quests;
+mod salo;
+mod season;
+mod slaughter;
+mod stable;
+mod top;
+mod trade;
+mod vet;
+mod world_events;
+
+pub use achievements::spawn_leader_job;
+pub use season::spawn_season_job;
+pub use world_events::spawn_world_event_job;
+
+/// Key admins pass to /enable and /disable; background jobs skip chats that turned it off.
+pub const MODULE_KEY: &str = "pig";
+/// Custom role that may change the game's chat settings without being a chat admin.
+pub const GAME_MASTER: &str = "gamemaster";
 
 pub struct PigGameModule;
 
@@ -26,14 +58,109 @@ impl BotModule for PigGameModule {
         "Pig Game"
     }
 
-    fn commands(&self) -> Vec<(&'static str, &'static str)> {
+    fn key(&self) -> &'static str {
+        MODULE_KEY
+    }
+
+    fn commands(&self) -> Vec<CommandSpec> {
         vec![
-            ("pig", "Создать новую свинью"),
-            ("grow", "Покормить свинью"),
-            ("my", "Посмотреть информацию о своей свинье"),
-            ("pigstats", "Посмотреть статистику свиней"),
-            ("top", "Посмотреть топ свиней по весу"),
-            ("name", "Поменять имя")
+            CommandSpec::new("pig", "Создать новую свинью")
+                .translate("en", "Create a new pig")
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("grow", "Покормить свинью")
+                .translate("en", "Feed your pig")
+                .alias("гров")
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("my", "Посмотреть информацию о своей свинье")
+                .translate("en", "Your pig's info"),
+            CommandSpec::new("pigstats", "Посмотреть статистику свиней")
+                .translate("en", "Pig stats")
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("top", "Топ свиней: вес, сало, рост, победы")
+                .translate("en", "Pig leaderboard: weight, salo, growth, wins")
+                .optional("сортировка", ArgKind::Word),
+            CommandSpec::new("globaltop", "Глобальный топ свиней всех чатов")
+                .translate("en", "Global leaderboard across chats")
+                .optional("on|off", ArgKind::Word),
+            CommandSpec::new("profile", "Все ваши свиньи во всех чатах")
+                .translate("en", "Your pigs in all chats"),
+            CommandSpec::new("season", "Текущий сезон и время до его конца")
+                .translate("en", "Current season and time left"),
+            CommandSpec::new("halloffame", "Победители прошлых сезонов")
+                .translate("en", "Past season winners"),
+            CommandSpec::new("achievements", "Достижения свиньи")
+                .translate("en", "Pig achievements")
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("quests", "Ежедневные задания")
+                .translate("en", "Daily quests")
+                .optional("tz", ArgKind::Word)
+                .optional("часы", ArgKind::Integer),
+            CommandSpec::new("breed", "Скрестить свою свинью с чужой")
+                .translate("en", "Breed your pig with someone else's")
+                .scope(CommandScope::Groups)
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("piglets", "Поросята в стойле")
+                .translate("en", "Piglets in the pen"),
+            CommandSpec::new("raise", "Вырастить поросёнка в свинарнике")
+                .translate("en", "Raise a piglet into your pigsty")
+                .arg("номер", ArgKind::Integer),
+            CommandSpec::new("select", "Выбрать активную свинью")
+                .translate("en", "Choose the active pig"),
+            CommandSpec::new("slaughter", "Забить свинью ради сала и престижа")
+                .translate("en", "Slaughter your pig for salo and prestige"),
+            CommandSpec::new("graveyard", "Кладбище забитых свиней")
+                .translate("en", "Graveyard of slaughtered pigs"),
+            CommandSpec::new("events", "События мира в чате")
+                .translate("en", "World events in this chat")
+                .optional("on|off", ArgKind::Word),
+            CommandSpec::new("name", "Поменять имя")
+                .translate("en", "Rename your pig")
+                .arg("имя", ArgKind::Rest),
+            CommandSpec::new("salo", "Собрать сало со свиньи")
+                .translate("en", "Collect salo from your pig"),
+            CommandSpec::new("build", "Постройки и их улучшение")
+                .translate("en", "Buildings and upgrades"),
+            CommandSpec::new("pill", "Покормить свинью с таблеткой")
+                .translate("en", "Feed your pig a pill"),
+            CommandSpec::new("poison", "Отравить чужую свинью")
+                .translate("en", "Poison someone else's pig")
+                .scope(CommandScope::Groups)
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("vet", "Вылечить свинью и собрать доход ветклиники")
+                .translate("en", "Heal your pig and collect vet clinic income"),
+            CommandSpec::new("inventory", "Посмотреть инвентарь")
+                .translate("en", "Your inventory"),
+            CommandSpec::new("give", "Подарить предмет другому игроку")
+                .translate("en", "Give an item to another player")
+                .scope(CommandScope::Groups)
+                .arg("номер", ArgKind::Integer)
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("trade", "Предложить обмен предметами")
+                .translate("en", "Offer an item trade")
+                .scope(CommandScope::Groups)
+                .arg("твой номер", ArgKind::Integer)
+                .arg("его номер", ArgKind::Integer)
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("sell", "Выставить предмет на рынок")
+                .translate("en", "Put an item up for sale")
+                .scope(CommandScope::Groups)
+                .arg("номер", ArgKind::Integer)
+                .arg("цена", ArgKind::Integer),
+            CommandSpec::new("market", "Рынок предметов чата")
+                .translate("en", "The chat's item market")
+                .scope(CommandScope::Groups)
+                .optional("history", ArgKind::Word),
+            CommandSpec::new("duel", "Вызвать свинью на дуэль на вес")
+                .translate("en", "Challenge a pig to a duel for weight")
+                .scope(CommandScope::Groups)
+                .arg("ставка", ArgKind::Integer)
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("duelstats", "История дуэлей свиньи")
+                .translate("en", "Pig duel history")
+                .optional("имя", ArgKind::Rest),
+            CommandSpec::new("history", "График веса свиньи")
+                .translate("en", "Pig weight chart")
+                .optional("имя", ArgKind::Rest),
         ]
     }
 
@@ -41,21 +168,32 @@ impl BotModule for PigGameModule {
         &self,
         bot: Bot,
         msg: Message,
-        command: &str,
-        args: Vec<&str>,
+        command: &ParsedCommand,
         db: &Database,
         config: &Config,
     ) -> ResponseResult<()> {
-        self.handle_pig_command(bot, msg, command, args, db, config).await
+        self.handle_pig_command(bot, msg, command, db, config).await
+    }
+
+    fn roles(&self) -> Vec<&'static str> {
+        vec![GAME_MASTER]
+    }
+
+    fn callback_namespaces(&self) -> Vec<&'static str> {
+        vec![
+            "grow", "back", "remove", "build", "inv", "eq", "top", "hof", "quests", "breed", "select",
+            "slaughter", "market", "trade", "duel",
+        ]
     }
 
     async fn handle_callback_query(
           &self,
           bot: Bot,
           query: CallbackQuery,
+          data: &CallbackData,
           db: &Database,
           config: &Config,
       ) -> ResponseResult<()> {
-          self.handle_callback_query(bot, query, db, config).await
+          self.handle_callback_query(bot, query, data, db, config).await
       }
 }
@@ -0,0 +1,94 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::config::Config;
+use crate::database::{Database, Pig};
+use super::buildings::Building;
+use super::cooldown::FeedOutcome;
+use super::events::GameEvent;
+use super::helpers::roll_growth;
+
+/// Upper bound for the pill success chance, so biolab upgrades never make pills risk-free.
+const MAX_PILLS_CHANCE_GROW: f64 = 0.95;
+
+pub enum PillEffect {
+    Boost(i32),
+    Poison,
+}
+
+impl super::PigGameModule {
+    /// Chance to find a pill on a feed, improved by the institute.
+    pub fn pill_drop_chance(&self, pig: &Pig, config: &Config) -> f64 {
+        (config.game.base_pills_chance + self.building_effect(Building::Institute, pig, config)).clamp(0.0, 1.0)
+    }
+
+    /// Chance that a swallowed pill boosts growth instead of poisoning, improved by the biolab.
+    pub fn pill_grow_chance(&self, pig: &Pig, config: &Config) -> f64 {
+        (config.game.base_pills_chance_grow + self.building_effect(Building::Biolab, pig, config))
+            .clamp(0.0, MAX_PILLS_CHANCE_GROW)
+    }
+
+    /// A successful pill always beats the regular grow ceiling.
+    pub fn roll_pill(&self, pig: &Pig, max_grow: i32, config: &Config) -> PillEffect {
+        if rand::random::<f64>() < self.pill_grow_chance(pig, config) {
+            let ceiling = max_grow.max(1);
+            PillEffect::Boost(ceiling + roll_growth(0, ceiling / 2))
+        } else {
+            PillEffect::Poison
+        }
+    }
+
+    pub async fn handle_pill_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let mut pig = match db.get_pig(chat_id, user_id).await {
+            Ok(Some(pig)) => pig,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if pig.pills <= 0 {
+            bot.send_message(msg.chat.id, "💊 У вас нет таблеток. Они иногда попадаются при кормёжке")
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        }
+
+        match self.feed_pig(&mut pig, db, config, true).await {
+            Ok(FeedOutcome::Fed { message, growth }) => {
+                bot.send_message(msg.chat.id, message).await?;
+                self.emit_event(&bot, msg.chat.id, user_id, GameEvent::Fed { growth }, db, config).await;
+            }
+            Ok(FeedOutcome::Cooldown(remaining)) => {
+                bot.send_message(msg.chat.id, self.format_cooldown_message(&pig.name, remaining))
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to feed pig: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка при кормлении свиньи")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
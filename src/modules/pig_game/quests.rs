@@ -0,0 +1,340 @@
+use rand::prelude::*;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, InlineKeyboardMarkup, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{DailyQuest, Database};
+use crate::modules::permissions::{Role, deny, has_role};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::helpers::{format_duration, unix_now};
+
+const DAY_SECS: i64 = 24 * 3600;
+/// Quests handed out per player and day.
+const QUESTS_PER_DAY: usize = 3;
+const PROGRESS_BAR_WIDTH: i32 = 10;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuestKind {
+    Feed,
+    WinDuel,
+    GainWeight,
+    CollectSalo,
+}
+
+impl QuestKind {
+    pub const ALL: [QuestKind; 4] = [QuestKind::Feed, QuestKind::WinDuel, QuestKind::GainWeight, QuestKind::CollectSalo];
+
+    pub fn key(self) -> &'static str {
+        match self {
+            QuestKind::Feed => "feed",
+            QuestKind::WinDuel => "win_duel",
+            QuestKind::GainWeight => "gain_weight",
+            QuestKind::CollectSalo => "collect_salo",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|kind| kind.key() == key)
+    }
+
+    /// Inclusive target range of a freshly rolled quest.
+    fn target_range(self) -> (i32, i32) {
+        match self {
+            QuestKind::Feed => (2, 4),
+            QuestKind::WinDuel => (1, 2),
+            QuestKind::GainWeight => (20, 60),
+            QuestKind::CollectSalo => (1, 2),
+        }
+    }
+
+    /// Reward relative to `QUEST_REWARD` for the hardest target of the range.
+    fn reward_factor(self) -> f64 {
+        match self {
+            QuestKind::Feed => 1.0,
+            QuestKind::WinDuel => 1.5,
+            QuestKind::GainWeight => 1.0,
+            QuestKind::CollectSalo => 0.5,
+        }
+    }
+
+    fn describe(self, target: i32) -> String {
+        match self {
+            QuestKind::Feed => format!("Покормить свинью {} раз", target),
+            QuestKind::WinDuel => format!("Выиграть дуэлей: {}", target),
+            QuestKind::GainWeight => format!("Набрать {} кг", target),
+            QuestKind::CollectSalo => format!("Собрать сало {} раз", target),
+        }
+    }
+}
+
+/// Day number in the chat's time zone; quests reset when it changes.
+pub fn quest_day(now: f64, utc_offset: i32) -> i64 {
+    (now as i64 + utc_offset as i64 * 3600).div_euclid(DAY_SECS)
+}
+
+pub fn secs_until_quest_reset(now: f64, utc_offset: i32) -> u64 {
+    (DAY_SECS - (now as i64 + utc_offset as i64 * 3600).rem_euclid(DAY_SECS)) as u64
+}
+
+/// Rolls `QUESTS_PER_DAY` distinct quests as `(kind, target, reward)`.
+pub fn roll_daily_quests(base_reward: i32) -> Vec<(QuestKind, i32, i32)> {
+    let mut rng = rand::rng();
+    QuestKind::ALL
+        .choose_multiple(&mut rng, QUESTS_PER_DAY)
+        .map(|&kind| {
+            let (min, max) = kind.target_range();
+            let target = rng.random_range(min..=max);
+            let reward = (base_reward as f64 * kind.reward_factor() * target as f64 / max as f64).ceil() as i32;
+            (kind, target, reward.max(1))
+        })
+        .collect()
+}
+
+fn progress_bar(progress: i32, target: i32) -> String {
+    let filled = (progress.clamp(0, target) * PROGRESS_BAR_WIDTH / target.max(1)) as usize;
+    format!("{}{}", "▰".repeat(filled), "▱".repeat(PROGRESS_BAR_WIDTH as usize - filled))
+}
+
+fn describe_quest(quest: &DailyQuest) -> String {
+    QuestKind::from_key(&quest.kind)
+        .map(|kind| kind.describe(quest.target))
+        .unwrap_or_else(|| quest.kind.clone())
+}
+
+impl super::PigGameModule {
+    /// Today's quests of a player, rolled on first access.
+    async fn ensure_daily_quests(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        day: i64,
+        db: &Database,
+        config: &Config,
+    ) -> Result<Vec<DailyQuest>, sqlx::Error> {
+        let quests = db.get_daily_quests(chat_id, user_id, day).await?;
+        if !quests.is_empty() {
+            return Ok(quests);
+        }
+
+        let rolled: Vec<(&str, i32, i32)> = roll_daily_quests(config.game.quest_reward)
+            .into_iter()
+            .map(|(kind, target, reward)| (kind.key(), target, reward))
+            .collect();
+        db.create_daily_quests(chat_id, user_id, day, &rolled).await?;
+        db.get_daily_quests(chat_id, user_id, day).await
+    }
+
+    /// Advances the player's quests by `(kind, amount)` steps and announces finished ones.
+    pub async fn advance_quests(
+        &self,
+        bot: &Bot,
+        chat_id: ChatId,
+        user_id: i64,
+        steps: &[(QuestKind, i32)],
+        db: &Database,
+        config: &Config,
+    ) -> Result<(), sqlx::Error> {
+        if steps.iter().all(|&(_, amount)| amount <= 0) || db.get_pig(chat_id.0, user_id).await?.is_none() {
+            return Ok(());
+        }
+
+        let settings = db.get_chat_settings(chat_id.0).await?;
+        let day = quest_day(unix_now(), settings.utc_offset);
+        self.ensure_daily_quests(chat_id.0, user_id, day, db, config).await?;
+
+        let mut completed = Vec::new();
+        for &(kind, amount) in steps.iter().filter(|&&(_, amount)| amount > 0) {
+            completed.extend(db.advance_daily_quest(chat_id.0, user_id, day, kind.key(), amount).await?);
+        }
+        if completed.is_empty() {
+            return Ok(());
+        }
+
+        let owner = db.get_pig(chat_id.0, user_id).await?.map(|pig| pig.owner_name).unwrap_or_default();
+        let lines: Vec<String> = completed
+            .iter()
+            .map(|quest| format!("📜 {} выполнил задание «{}» и получил {} 🥓", owner, describe_quest(quest), quest.reward))
+            .collect();
+        if let Err(e) = bot.send_message(chat_id, lines.join("\n")).await {
+            log::error!("Failed to announce quest completion: {}", e);
+        }
+        Ok(())
+    }
+
+    async fn render_quests(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        db: &Database,
+        config: &Config,
+    ) -> Result<Option<(String, InlineKeyboardMarkup)>, sqlx::Error> {
+        let Some(pig) = db.get_pig(chat_id, user_id).await? else {
+            return Ok(None);
+        };
+
+        let now = unix_now();
+        let settings = db.get_chat_settings(chat_id).await?;
+        let quests = self
+            .ensure_daily_quests(chat_id, user_id, quest_day(now, settings.utc_offset), db, config)
+            .await?;
+
+        let mut lines = vec![format!("📜 Задания на сегодня для {}", pig.owner_name)];
+        for quest in &quests {
+            lines.push(String::new());
+            lines.push(format!(
+                "{} {} (+{} 🥓)",
+                if quest.completed { "✅" } else { "▫️" },
+                describe_quest(quest),
+                quest.reward
+            ));
+            lines.push(format!("{} {}/{}", progress_bar(quest.progress, quest.target), quest.progress, quest.target));
+        }
+        lines.push(String::new());
+        lines.push(format!(
+            "⏳ Новые задания через {} (UTC{:+})",
+            format_duration(secs_until_quest_reset(now, settings.utc_offset)),
+            settings.utc_offset
+        ));
+
+        Ok(Some((lines.join("\n"), self.create_quests_keyboard(user_id))))
+    }
+
+    /// `/quests` shows today's quests; `/quests tz <hours>` moves the chat's reset (admins and game masters only).
+    pub async fn handle_quests_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        if args.first() == Some(&"tz") {
+            let Some(offset) = args.get(1).and_then(|s| s.parse::<i32>().ok()).filter(|o| (-12..=14).contains(o)) else {
+                bot.send_message(msg.chat.id, "Использование: /quests tz <смещение от UTC в часах, от -12 до 14>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            };
+
+            let Some(user) = msg.from.as_ref() else {
+                return Ok(());
+            };
+            let role = Role::Custom(super::GAME_MASTER);
+            if !has_role(&bot, db, config, msg.chat.id, user.id, role).await {
+                return deny(&bot, &msg, role).await;
+            }
+
+            let text = match db.set_utc_offset(chat_id, offset).await {
+                Ok(()) => format!("🕛 Задания теперь обновляются в полночь по UTC{:+}", offset),
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных".to_string()
+                }
+            };
+            bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        match self.render_quests(chat_id, user_id, db, config).await {
+            Ok(Some((text, keyboard))) => {
+                bot.send_message(msg.chat.id, text).reply_markup(keyboard).await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `quests:<user_id>` progress refreshes.
+    pub async fn handle_quests_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let user_id = query.from.id.0 as i64;
+        let Some(original_user_id) = data.get::<i64>(0) else {
+            return answer_stale(bot, query).await;
+        };
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id).text("🖕🤣 Это не твои задания!").await?;
+            return Ok(());
+        }
+
+        match self.render_quests(message.chat().id.0, user_id, db, config).await {
+            Ok(Some((text, keyboard))) => {
+                // Telegram rejects edits that change nothing.
+                if let Err(e) = bot
+                    .edit_message_text(message.chat().id, message.id(), text)
+                    .reply_markup(keyboard)
+                    .await
+                {
+                    log::debug!("Quests not edited: {}", e);
+                }
+                bot.answer_callback_query(&query.id).await?;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id).text("У вас нет свиньи!").await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn quest_day_follows_chat_time_zone() {
+        // 2024-01-01 22:30 UTC is already January 2nd in UTC+3.
+        let now = 1_704_148_200.0;
+        assert_eq!(quest_day(now, 3), quest_day(now, 0) + 1);
+        assert_eq!(quest_day(now, -5), quest_day(now, 0));
+        assert_eq!(secs_until_quest_reset(now, 0), 90 * 60);
+        assert_eq!(secs_until_quest_reset(now, 3), (22 * 60 + 30) * 60);
+    }
+
+    #[test]
+    fn rolled_quests_are_distinct_and_in_range() {
+        for _ in 0..50 {
+            let quests = roll_daily_quests(30);
+            assert_eq!(quests.len(), QUESTS_PER_DAY);
+            for (i, (kind, target, reward)) in quests.iter().enumerate() {
+                let (min, max) = kind.target_range();
+                assert!((min..=max).contains(target));
+                assert!(*reward > 0);
+                assert!(quests[i + 1..].iter().all(|other| other.0 != *kind));
+            }
+        }
+    }
+
+    #[test]
+    fn progress_bar_is_fixed_width() {
+        assert_eq!(progress_bar(0, 3), "▱▱▱▱▱▱▱▱▱▱");
+        assert_eq!(progress_bar(5, 3), "▰▰▰▰▰▰▰▰▰▰");
+        assert_eq!(progress_bar(1, 2).chars().count(), PROGRESS_BAR_WIDTH as usize);
+    }
+}
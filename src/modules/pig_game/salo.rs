@@ -0,0 +1,90 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::database::Database;
+use crate::config::Config;
+use super::events::GameEvent;
+use super::helpers::{format_duration, unix_now, MAX_SALO_PERIODS};
+
+impl super::PigGameModule {
+    pub async fn handle_salo_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let pig = match db.get_pig(chat_id, user_id).await {
+            Ok(Some(pig)) => pig,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let now = unix_now();
+        let period = self.salo_period_secs(&pig, config);
+        let multiplier = self.salo_multiplier(&pig, config);
+        let (amount, periods) = self.calculate_salo_yield(now - pig.last_salo, period, pig.weight, multiplier);
+
+        if periods == 0 {
+            let remaining = (pig.last_salo + period - now).max(0.0).ceil() as u64;
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "🥓 Сало ещё не нагулялось. Следующая партия через {}\n🥓 На складе: {}",
+                    format_duration(remaining),
+                    pig.salo
+                ),
+            )
+            .reply_to(msg.id)
+            .await?;
+            return Ok(());
+        }
+
+        // Keep the partial period unless the storage overflowed.
+        let new_last_salo = if periods >= MAX_SALO_PERIODS {
+            now
+        } else {
+            pig.last_salo + periods as f64 * period
+        };
+
+        match db.collect_salo(chat_id, user_id, amount, pig.last_salo, new_last_salo).await {
+            Ok(Some(updated)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!(
+                        "🥓 {} отдал {} сала.\n🥓 На складе: {}",
+                        updated.name, amount, updated.salo
+                    ),
+                )
+                .reply_to(msg.id)
+                .await?;
+                self.emit_event(&bot, msg.chat.id, user_id, GameEvent::SaloCollected, db, config).await;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "🥓 Это сало уже собрали")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to collect salo: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
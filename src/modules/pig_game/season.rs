@@ -0,0 +1,284 @@
+use std::sync::Arc;
+use std::time::Duration;
+use teloxide::{
+    prelude::*,
+    types::{CallbackQuery, InlineKeyboardMarkup, MaybeInaccessibleMessage, Message},
+};
+use crate::config::{Config, GameConfig};
+use crate::database::{Database, LeaderboardSort, Season, SeasonStanding};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::helpers::{format_duration, unix_now};
+
+/// How often the background job looks for seasons to close.
+const SEASON_CHECK_INTERVAL: Duration = Duration::from_secs(10 * 60);
+/// Pigs archived per finished season.
+const SEASON_ARCHIVE_SIZE: i64 = 10;
+/// Places that earn a title.
+pub const SEASON_PODIUM: i32 = 3;
+/// Finished seasons per /halloffame page.
+const HALL_OF_FAME_PAGE_SIZE: i64 = 3;
+
+pub fn season_length_secs(config: &GameConfig) -> f64 {
+    (config.season_length * 24 * 3600) as f64
+}
+
+pub fn season_title(position: i32, number: i32) -> Option<String> {
+    let title = match position {
+        1 => "👑 Чемпион",
+        2 => "🥈 Вице-чемпион",
+        3 => "🥉 Бронзовый призёр",
+        _ => return None,
+    };
+    Some(format!("{} сезона {}", title, number))
+}
+
+fn format_standing(standing: &SeasonStanding, number: i32) -> String {
+    match season_title(standing.position, number) {
+        Some(title) => format!("{}: {} - {} кг (владелец: {})", title, standing.pig_name, standing.weight, standing.owner_name),
+        None => format!("{}. {} - {} кг (владелец: {})", standing.position, standing.pig_name, standing.weight, standing.owner_name),
+    }
+}
+
+fn format_season_results(season: &Season, standings: &[SeasonStanding], config: &GameConfig) -> String {
+    let mut lines = vec![format!("🏁 Сезон {} окончен!", season.number)];
+    if standings.is_empty() {
+        lines.push("Никто так и не вырастил свинью 🐖".to_string());
+    } else {
+        lines.push(String::new());
+        lines.extend(standings.iter().map(|standing| format_standing(standing, season.number)));
+    }
+    lines.push(String::new());
+    if config.season_weight_keep <= 0.0 {
+        lines.push("⚖️ Все свиньи похудели до 1 кг".to_string());
+    } else {
+        lines.push(format!("⚖️ Свиньи сохранили {:.0}% веса", config.season_weight_keep * 100.0));
+    }
+    lines.push(format!("🚀 Начинается сезон {}!", season.number + 1));
+    lines.join("\n")
+}
+
+/// Spawns the job that closes expired seasons and announces the winners.
+/// A `SEASON_LENGTH` of zero disables seasons; chats with the game switched
+/// off keep their season open until it is back on.
+pub fn spawn_season_job(bot: Bot, db: Arc<Database>, config: Arc<Config>) {
+    if config.game.season_length == 0 {
+        log::info!("Seasons are disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(SEASON_CHECK_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = close_expired_seasons(&bot, &db, &config.game).await {
+                log::error!("Season job failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn close_expired_seasons(bot: &Bot, db: &Database, config: &GameConfig) -> Result<(), sqlx::Error> {
+    let now = unix_now();
+    db.start_missing_seasons(now, super::MODULE_KEY).await?;
+
+    for season in db.get_expired_seasons(now - season_length_secs(config), super::MODULE_KEY).await? {
+        let Some((ended, standings)) = db
+            .end_season(season.id, now, config.season_weight_keep, SEASON_ARCHIVE_SIZE)
+            .await?
+        else {
+            continue;
+        };
+
+        log::info!("Season {} of chat {} ended", ended.number, ended.chat_id);
+        let text = format_season_results(&ended, &standings, config);
+        if let Err(e) = bot.send_message(ChatId(ended.chat_id), text).await {
+            log::warn!("Failed to announce season results in chat {}: {}", ended.chat_id, e);
+        }
+    }
+
+    Ok(())
+}
+
+impl super::PigGameModule {
+    /// `/season`: current season number, time left and leaders.
+    pub async fn handle_season_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+
+        if config.game.season_length == 0 {
+            bot.send_message(msg.chat.id, "Сезоны отключены, жрём без остановки 🐖").await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let status = async {
+            let season = db.get_or_start_season(chat_id, now).await?;
+            let leaders = db.get_leaderboard(chat_id, LeaderboardSort::Weight, now, SEASON_PODIUM as i64, 0).await?;
+            Ok::<_, sqlx::Error>((season, leaders))
+        };
+
+        match status.await {
+            Ok((season, leaders)) => {
+                let remaining = (season.started_at + season_length_secs(&config.game) - now).max(0.0) as u64;
+                let mut lines = vec![
+                    format!("📅 Сезон {}", season.number),
+                    format!("⏳ До конца: {}", format_season_remaining(remaining)),
+                ];
+                if !leaders.is_empty() {
+                    lines.push(String::new());
+                    lines.push("Сейчас лидируют:".to_string());
+                    for (i, entry) in leaders.iter().enumerate() {
+                        lines.push(format!("{}. {} - {} кг (владелец: {})", i + 1, entry.name, entry.score, entry.owner_name));
+                    }
+                }
+                bot.send_message(msg.chat.id, lines.join("\n")).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn render_hall_of_fame(
+        &self,
+        chat_id: i64,
+        page: usize,
+        db: &Database,
+    ) -> Result<Option<(String, InlineKeyboardMarkup)>, sqlx::Error> {
+        let total = db.count_finished_seasons(chat_id).await?;
+        if total == 0 {
+            return Ok(None);
+        }
+
+        let pages = (total as usize).div_ceil(HALL_OF_FAME_PAGE_SIZE as usize);
+        let page = page.min(pages - 1);
+        let seasons = db
+            .get_finished_seasons(chat_id, HALL_OF_FAME_PAGE_SIZE, page as i64 * HALL_OF_FAME_PAGE_SIZE)
+            .await?;
+
+        let mut lines = vec!["🏛 Зал славы".to_string()];
+        for season in &seasons {
+            lines.push(String::new());
+            let ended = season
+                .ended_at
+                .and_then(|ts| chrono::DateTime::from_timestamp(ts as i64, 0))
+                .map(|date| format!(" (завершён {})", date.format("%d.%m.%Y")))
+                .unwrap_or_default();
+            lines.push(format!("📅 Сезон {}{}", season.number, ended));
+            let podium = db.get_season_standings(season.id, SEASON_PODIUM as i64).await?;
+            if podium.is_empty() {
+                lines.push("Без победителей".to_string());
+            }
+            lines.extend(podium.iter().map(|standing| format_standing(standing, season.number)));
+        }
+        lines.push(String::new());
+        lines.push(format!("Страница {}/{}", page + 1, pages));
+
+        Ok(Some((lines.join("\n"), self.create_hall_of_fame_keyboard(page, pages))))
+    }
+
+    /// `/halloffame`: podiums of finished seasons, newest first.
+    pub async fn handle_hall_of_fame_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        match self.render_hall_of_fame(msg.chat.id.0, 0, db).await {
+            Ok(Some((text, keyboard))) => {
+                bot.send_message(msg.chat.id, text).reply_markup(keyboard).await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "🏛 Зал славы пуст: ещё ни один сезон не закончился").await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `hof:<page>`; anyone in the chat may browse.
+    pub async fn handle_hall_of_fame_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let Some(page) = data.get::<usize>(0) else {
+            return answer_stale(bot, query).await;
+        };
+
+        match self.render_hall_of_fame(message.chat().id.0, page, db).await {
+            Ok(Some((text, keyboard))) => {
+                bot.edit_message_text(message.chat().id, message.id(), text)
+                    .reply_markup(keyboard)
+                    .await?;
+                bot.answer_callback_query(&query.id).await?;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id).text("Зал славы пуст").await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Podium titles of a pig's owner in this chat, for the pig card.
+    pub async fn format_season_titles(&self, chat_id: i64, user_id: i64, db: &Database) -> Option<String> {
+        let titles = match db.get_season_titles(chat_id, user_id, SEASON_PODIUM).await {
+            Ok(titles) => titles,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                return None;
+            }
+        };
+        let titles: Vec<String> = titles
+            .into_iter()
+            .filter_map(|(number, position)| season_title(position, number))
+            .collect();
+        (!titles.is_empty()).then(|| titles.join("\n"))
+    }
+}
+
+fn format_season_remaining(secs: u64) -> String {
+    let days = secs / (24 * 3600);
+    if days == 0 {
+        return format_duration(secs);
+    }
+    format!("{} дн. {}", days, format_duration(secs % (24 * 3600)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_podium_earns_titles() {
+        assert_eq!(season_title(1, 4).as_deref(), Some("👑 Чемпион сезона 4"));
+        assert!(season_title(3, 1).is_some());
+        assert_eq!(season_title(4, 1), None);
+    }
+
+    #[test]
+    fn remaining_time_switches_to_days() {
+        assert_eq!(format_season_remaining(90 * 60), "1 ч 30 мин");
+        assert_eq!(format_season_remaining(2 * 24 * 3600 + 3 * 3600), "2 дн. 3 ч");
+    }
+}
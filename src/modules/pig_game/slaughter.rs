@@ -0,0 +1,201 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{Database, Pig};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::helpers::unix_now;
+
+/// Graves listed by /graveyard.
+const GRAVEYARD_SIZE: i64 = 10;
+
+impl super::PigGameModule {
+    /// Salo per kilogram a slaughter pays for this pig, butchery included.
+    fn slaughter_rate(&self, pig: &Pig, config: &Config) -> f64 {
+        config.game.slaughter_salo_per_kg * self.salo_multiplier(pig, config)
+    }
+
+    /// `/slaughter`: offers to butcher the active pig for salo and prestige.
+    pub async fn handle_slaughter_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let loaded = async {
+            let Some(pig) = db.get_pig(chat_id, user_id).await? else {
+                return Ok(None);
+            };
+            let prestige = db.get_prestige(chat_id, user_id).await?;
+            Ok::<_, sqlx::Error>(Some((pig, prestige)))
+        };
+
+        let (pig, prestige) = match loaded.await {
+            Ok(Some(loaded)) => loaded,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if pig.weight < config.game.slaughter_min_weight {
+            bot.send_message(
+                msg.chat.id,
+                format!(
+                    "🔪 {} слишком тощая для забоя: {} из {} кг. Откорми сначала",
+                    pig.name, pig.weight, config.game.slaughter_min_weight
+                ),
+            )
+            .reply_to(msg.id)
+            .await?;
+            return Ok(());
+        }
+
+        let salo = (pig.weight as f64 * self.slaughter_rate(&pig, config)).floor() as i32;
+        bot.send_message(
+            msg.chat.id,
+            format!(
+                "🔪 Пустить {} ({} кг) под нож?\n🥓 Получишь {} сала\n⭐ Престиж: {} → {} (+{:.0}% к росту)\n\nСвинья уйдёт на кладбище, а вместо неё родится новая с нуля",
+                pig.name,
+                pig.weight,
+                salo,
+                prestige,
+                prestige + 1,
+                config.game.prestige_bonus * (prestige + 1) as f64 * 100.0
+            ),
+        )
+        .reply_markup(self.create_slaughter_keyboard(user_id, pig.id))
+        .await?;
+
+        Ok(())
+    }
+
+    /// Handles `slaughter:<user_id>:<pig_id>:a|d` presses.
+    pub async fn handle_slaughter_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let (Some(original_user_id), Some(pig_id)) = (data.get::<i64>(0), data.get::<i32>(1)) else {
+            return answer_stale(bot, query).await;
+        };
+        let confirm = data.field(2) == Some("a");
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id)
+                .text("🖕🤣 Руки прочь от чужой свиньи!")
+                .await?;
+            return Ok(());
+        }
+
+        if !confirm {
+            bot.edit_message_text(message.chat().id, message.id(), "🐷 Свинья помилована. Пусть пока хрюкает")
+                .await?;
+            bot.answer_callback_query(&query.id).await?;
+            return Ok(());
+        }
+
+        let slaughtered = async {
+            let pig = db
+                .get_stable_pigs(chat_id, user_id)
+                .await?
+                .into_iter()
+                .map(|stable_pig| stable_pig.pig)
+                .find(|pig| pig.id == pig_id);
+            let Some(pig) = pig else {
+                return Ok(None);
+            };
+            let new_name = self.generate_default_pig_name();
+            let grave = db
+                .slaughter_pig(pig.id, config.game.slaughter_min_weight, self.slaughter_rate(&pig, config), &new_name, unix_now())
+                .await?;
+            Ok::<_, sqlx::Error>(grave.map(|grave| (grave, new_name)))
+        };
+
+        match slaughtered.await {
+            Ok(Some((grave, new_name))) => {
+                bot.edit_message_text(
+                    message.chat().id,
+                    message.id(),
+                    format!(
+                        "🔪 {} ({} кг) пущена на сало. Покойся с миром 🪦\n🥓 +{} сала\n⭐ Престиж: {} (+{:.0}% к росту)\n\n🐽 В свинарнике уже хрюкает новая свинья: {}. Переименовать можно через /name",
+                        grave.name,
+                        grave.weight,
+                        grave.salo,
+                        grave.prestige,
+                        config.game.prestige_bonus * grave.prestige as f64 * 100.0,
+                        new_name
+                    ),
+                )
+                .await?;
+                bot.answer_callback_query(&query.id).text("Свинья забита").await?;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id)
+                    .text("Эту свинью уже не забить")
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to slaughter pig: {}", e);
+                bot.answer_callback_query(&query.id)
+                    .text("Ошибка базы данных")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `/graveyard`: the chat's most recently slaughtered pigs.
+    pub async fn handle_graveyard_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        match db.get_graveyard(msg.chat.id.0, GRAVEYARD_SIZE).await {
+            Ok(graves) if graves.is_empty() => {
+                bot.send_message(msg.chat.id, "🪦 Кладбище пустое. Пока все свиньи живы").await?;
+            }
+            Ok(graves) => {
+                let mut lines = vec!["🪦 Кладбище свиней:".to_string()];
+                for grave in &graves {
+                    let date = chrono::DateTime::from_timestamp(grave.slaughtered_at as i64, 0)
+                        .map(|date| date.format("%d.%m.%Y").to_string())
+                        .unwrap_or_default();
+                    lines.push(format!(
+                        "✝️ {} ({}) — {} кг, {} 🥓, ⭐{} · {}",
+                        grave.name, grave.owner_name, grave.weight, grave.salo, grave.prestige, date
+                    ));
+                }
+                bot.send_message(msg.chat.id, lines.join("\n")).await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,175 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{Database, StablePig};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::buildings::Building;
+use super::helpers::unix_now;
+
+impl super::PigGameModule {
+    /// Pigs a player may keep in a chat: one, plus the places of their
+    /// pigsty. Buildings belong to the farm, so every pig shows the same level.
+    pub fn max_pigs(&self, pigs: &[StablePig], config: &Config) -> i64 {
+        let places = pigs
+            .first()
+            .map_or(0, |p| self.building_effect(Building::Pigsty, &p.pig, config).round() as i64);
+        1 + places
+    }
+
+    fn format_stable(&self, pigs: &[StablePig], config: &Config) -> String {
+        let mut lines = vec![format!("🛖 Свинарник ({}/{}):", pigs.len(), self.max_pigs(pigs, config))];
+        for (i, stable_pig) in pigs.iter().enumerate() {
+            lines.push(format!(
+                "{}. {} {} — {} кг{}",
+                i + 1,
+                if stable_pig.active { "✅" } else { "🐖" },
+                stable_pig.pig.name,
+                stable_pig.pig.weight,
+                if stable_pig.active { " (активная)" } else { "" }
+            ));
+        }
+        lines.push("\nКоманды действуют на активную свинью. Выбери её кнопкой ниже".to_string());
+        lines.join("\n")
+    }
+
+    /// `/pig <name>` for a player who already has a pig: puts another one in
+    /// the pigsty if there is room.
+    pub async fn handle_extra_pig(
+        &self,
+        bot: Bot,
+        msg: Message,
+        pig_name: &str,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+        let username = msg
+            .from
+            .as_ref()
+            .and_then(|u| u.username.as_deref())
+            .unwrap_or("Unknown");
+
+        let added = async {
+            let pigs = db.get_stable_pigs(chat_id, user_id).await?;
+            let max_pigs = self.max_pigs(&pigs, config);
+            let pig = db.add_stable_pig(chat_id, user_id, pig_name, username, max_pigs, unix_now()).await?;
+            Ok::<_, sqlx::Error>((pig, max_pigs))
+        };
+
+        match added.await {
+            Ok((Some(pig), _)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("🐷 {} завёл ещё одну свинью: {}! Она ждёт в свинарнике, выбрать её можно через /select", username, pig.name),
+                )
+                .await?;
+            }
+            Ok((None, max_pigs)) => {
+                bot.send_message(
+                    msg.chat.id,
+                    format!("🛖 В свинарнике нет места (максимум {}). Улучши свинарник через /build", max_pigs),
+                )
+                .reply_to(msg.id)
+                .await?;
+            }
+            Err(e) => {
+                log::error!("Failed to create pig: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка при создании свиньи").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// `/select`: lists the player's pigs with a button to make each one active.
+    pub async fn handle_select_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        match db.get_stable_pigs(chat_id, user_id).await {
+            Ok(pigs) if pigs.is_empty() => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+            }
+            Ok(pigs) => {
+                bot.send_message(msg.chat.id, self.format_stable(&pigs, config))
+                    .reply_markup(self.create_select_keyboard(user_id, &pigs))
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `select:<user_id>:<pig_id>` presses.
+    pub async fn handle_select_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let (Some(original_user_id), Some(pig_id)) = (data.get::<i64>(0), data.get::<i32>(1)) else {
+            return answer_stale(bot, query).await;
+        };
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id)
+                .text("🖕🤣 Это не твой свинарник!")
+                .await?;
+            return Ok(());
+        }
+
+        let selected = async {
+            let Some(pig) = db.set_active_pig(chat_id, user_id, pig_id).await? else {
+                return Ok(None);
+            };
+            let pigs = db.get_stable_pigs(chat_id, user_id).await?;
+            Ok::<_, sqlx::Error>(Some((pig, pigs)))
+        };
+
+        match selected.await {
+            Ok(Some((pig, pigs))) => {
+                bot.answer_callback_query(&query.id)
+                    .text(format!("✅ Теперь активна {}", pig.name))
+                    .await?;
+                bot.edit_message_text(message.chat().id, message.id(), self.format_stable(&pigs, config))
+                    .reply_markup(self.create_select_keyboard(user_id, &pigs))
+                    .await?;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id)
+                    .text("Этой свиньи больше нет")
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id)
+                    .text("Ошибка базы данных")
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,220 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, InlineKeyboardMarkup, MaybeInaccessibleMessage, Message},
+};
+use crate::database::{Database, LeaderboardEntry, LeaderboardSort};
+use crate::modules::callback::{CallbackData, answer_stale};
+use super::helpers::unix_now;
+
+pub const TOP_PAGE_SIZE: usize = 10;
+/// Window of the "growth" leaderboard, in seconds.
+const GROWTH_WINDOW: f64 = 24.0 * 3600.0;
+
+/// Accepts both the callback keys and Russian names of the sort modes.
+pub fn parse_leaderboard_sort(arg: &str) -> Option<LeaderboardSort> {
+    match arg.to_lowercase().as_str() {
+        "вес" => Some(LeaderboardSort::Weight),
+        "сало" => Some(LeaderboardSort::Salo),
+        "рост" | "прирост" => Some(LeaderboardSort::Growth),
+        "победы" | "дуэли" => Some(LeaderboardSort::Wins),
+        key => LeaderboardSort::from_key(key),
+    }
+}
+
+pub fn top_page_count(total: usize) -> usize {
+    total.div_ceil(TOP_PAGE_SIZE).max(1)
+}
+
+impl super::PigGameModule {
+    pub fn leaderboard_title(&self, sort: LeaderboardSort) -> &'static str {
+        match sort {
+            LeaderboardSort::Weight => "⚖️ Вес",
+            LeaderboardSort::Salo => "🥓 Сало",
+            LeaderboardSort::Growth => "📈 Рост за 24ч",
+            LeaderboardSort::Wins => "⚔️ Победы",
+        }
+    }
+
+    fn format_leaderboard_score(&self, sort: LeaderboardSort, score: i64) -> String {
+        match sort {
+            LeaderboardSort::Weight => format!("{} кг", score),
+            LeaderboardSort::Salo => format!("{} 🥓", score),
+            LeaderboardSort::Growth => format!("{:+} кг", score),
+            LeaderboardSort::Wins => format!("{} 🏆", score),
+        }
+    }
+
+    fn format_leaderboard_line(&self, sort: LeaderboardSort, position: i64, entry: &LeaderboardEntry) -> String {
+        let medal = match position {
+            1 => "🥇",
+            2 => "🥈",
+            3 => "🥉",
+            _ => "🏅",
+        };
+        format!(
+            "{} {}. {} - {} (владелец: {})",
+            medal,
+            position,
+            entry.name,
+            self.format_leaderboard_score(sort, entry.score),
+            entry.owner_name
+        )
+    }
+
+    /// Builds one page of the chat leaderboard with the viewer's own position at the bottom.
+    async fn render_top(
+        &self,
+        chat_id: i64,
+        user_id: i64,
+        sort: LeaderboardSort,
+        page: usize,
+        db: &Database,
+    ) -> Result<Option<(String, InlineKeyboardMarkup)>, sqlx::Error> {
+        let total = db.get_chat_total_players(chat_id).await? as usize;
+        if total == 0 {
+            return Ok(None);
+        }
+
+        let pages = top_page_count(total);
+        let page = page.min(pages - 1);
+        let since = unix_now() - GROWTH_WINDOW;
+        let offset = page * TOP_PAGE_SIZE;
+        let entries = db
+            .get_leaderboard(chat_id, sort, since, TOP_PAGE_SIZE as i64, offset as i64)
+            .await?;
+
+        let mut lines = vec![format!("🏆 Топ свиней в чате — {}", self.leaderboard_title(sort))];
+        for (i, entry) in entries.iter().enumerate() {
+            let line = self.format_leaderboard_line(sort, (offset + i + 1) as i64, entry);
+            lines.push(if entry.user_id == user_id { format!("{} 👈", line) } else { line });
+        }
+
+        match db.get_leaderboard_position(chat_id, user_id, sort, since).await? {
+            Some((position, entry)) => {
+                lines.push(String::new());
+                lines.push(format!("👤 Ты: {}", self.format_leaderboard_line(sort, position, &entry)));
+            }
+            None => {
+                lines.push(String::new());
+                lines.push("👤 У тебя нет свиньи! Создай её командой /pig <имя>".to_string());
+            }
+        }
+        lines.push(format!("Страница {}/{}", page + 1, pages));
+
+        Ok(Some((lines.join("\n"), self.create_top_keyboard(user_id, sort, page, pages))))
+    }
+
+    /// `/top [вес|сало|рост|победы]`
+    pub async fn handle_top_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let sort = match args.first() {
+            Some(arg) => match parse_leaderboard_sort(arg) {
+                Some(sort) => sort,
+                None => {
+                    bot.send_message(msg.chat.id, "Использование: /top [вес|сало|рост|победы]")
+                        .reply_to(msg.id)
+                        .await?;
+                    return Ok(());
+                }
+            },
+            None => LeaderboardSort::Weight,
+        };
+
+        match self.render_top(chat_id, user_id, sort, 0, db).await {
+            Ok(Some((text, keyboard))) => {
+                bot.send_message(msg.chat.id, text).reply_markup(keyboard).await?;
+            }
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "В этом чате пока нет свиней 🐖").await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Handles `top:<user_id>:<sort>:<page>` sort switches and pagination.
+    pub async fn handle_top_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        let chat_id = message.chat().id.0;
+        let user_id = query.from.id.0 as i64;
+
+        let (Some(original_user_id), Some(sort), Some(page)) = (
+            data.get::<i64>(0),
+            data.field(1).and_then(LeaderboardSort::from_key),
+            data.get::<usize>(2),
+        ) else {
+            return answer_stale(bot, query).await;
+        };
+
+        if user_id != original_user_id {
+            bot.answer_callback_query(&query.id)
+                .text("🖕🤣 Вызови свой /top и листай сколько хочешь")
+                .await?;
+            return Ok(());
+        }
+
+        match self.render_top(chat_id, user_id, sort, page, db).await {
+            Ok(Some((text, keyboard))) => {
+                // Telegram rejects edits that change nothing, e.g. a re-tapped sort button.
+                if let Err(e) = bot
+                    .edit_message_text(message.chat().id, message.id(), text)
+                    .reply_markup(keyboard)
+                    .await
+                {
+                    log::debug!("Leaderboard not edited: {}", e);
+                }
+                bot.answer_callback_query(&query.id).await?;
+            }
+            Ok(None) => {
+                bot.answer_callback_query(&query.id).text("В этом чате пока нет свиней 🐖").await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sort_keys_round_trip() {
+        for sort in LeaderboardSort::ALL {
+            assert_eq!(LeaderboardSort::from_key(sort.key()), Some(sort));
+            assert_eq!(parse_leaderboard_sort(sort.key()), Some(sort));
+        }
+        assert_eq!(parse_leaderboard_sort("Сало"), Some(LeaderboardSort::Salo));
+        assert_eq!(parse_leaderboard_sort("жир"), None);
+    }
+
+    #[test]
+    fn page_count_rounds_up_and_never_hits_zero() {
+        assert_eq!(top_page_count(0), 1);
+        assert_eq!(top_page_count(TOP_PAGE_SIZE), 1);
+        assert_eq!(top_page_count(TOP_PAGE_SIZE + 1), 2);
+    }
+}
@@ -0,0 +1,288 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, MaybeInaccessibleMessage, Message},
+};
+use crate::config::Config;
+use crate::database::{Database, Loot, LootOffer, OfferFailure};
+use crate::modules::callback::{CallbackData, answer_stale};
+use crate::modules::command::ParsedCommand;
+use super::helpers::unix_now;
+
+impl super::PigGameModule {
+    /// `/give <n>` and `/trade <n> <m>`, addressed by reply or by the target pig's name.
+    /// Item numbers are positions in each player's `/inventory`.
+    pub async fn handle_offer_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+        let is_trade = command.name == "trade";
+        let numbers_needed = if is_trade { 2 } else { 1 };
+
+        let numbers: Vec<usize> = (0..numbers_needed)
+            .filter_map(|i| command.integer(i))
+            .filter_map(|n| usize::try_from(n).ok())
+            .collect();
+        if numbers.len() < numbers_needed || numbers.contains(&0) {
+            let usage = if is_trade {
+                "Использование: /trade <номер вашего предмета> <номер его предмета> в ответ на сообщение игрока или с именем свиньи"
+            } else {
+                "Использование: /give <номер предмета> в ответ на сообщение игрока или с именем свиньи"
+            };
+            bot.send_message(msg.chat.id, usage).reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        let target_name = command.text(numbers_needed).unwrap_or_default();
+        let target = match self.resolve_target_pig(&msg, target_name, db).await {
+            Ok(Some(target)) => target,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Не нашёл, кому это отдать. Ответь на сообщение игрока со свиньёй или укажи имя свиньи")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if target.user_id == user_id {
+            bot.send_message(msg.chat.id, "Сам себе не подаришь").reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        let items = async {
+            let mine = db.get_user_loot(chat_id, user_id).await?;
+            let theirs = if is_trade {
+                db.get_user_loot(chat_id, target.user_id).await?
+            } else {
+                Vec::new()
+            };
+            Ok::<_, sqlx::Error>((mine, theirs))
+        };
+
+        let (mine, theirs) = match items.await {
+            Ok(items) => items,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let Some(offered) = mine.get(numbers[0] - 1) else {
+            bot.send_message(msg.chat.id, format!("У вас нет предмета №{}. Загляните в /inventory", numbers[0]))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let requested = if is_trade {
+            match theirs.get(numbers[1] - 1) {
+                Some(item) => Some(item),
+                None => {
+                    bot.send_message(msg.chat.id, format!("У {} нет предмета №{}", target.owner_name, numbers[1]))
+                        .reply_to(msg.id)
+                        .await?;
+                    return Ok(());
+                }
+            }
+        } else {
+            None
+        };
+
+        let expires_at = unix_now() + (config.game.trade_offer_ttl * 60) as f64;
+        let offer = match db
+            .create_loot_offer(chat_id, user_id, target.user_id, offered.uuid, requested.map(|item| item.uuid), expires_at)
+            .await
+        {
+            Ok(offer) => offer,
+            Err(e) => {
+                log::error!("Failed to create loot offer: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let text = self.format_offer(&offer, Some(offered), requested, db, config).await;
+        bot.send_message(msg.chat.id, text)
+            .reply_markup(self.create_offer_keyboard(offer.id))
+            .await?;
+
+        Ok(())
+    }
+
+    async fn format_offer(
+        &self,
+        offer: &LootOffer,
+        offered: Option<&Loot>,
+        requested: Option<&Loot>,
+        db: &Database,
+        config: &Config,
+    ) -> String {
+        let owner_name = |user_id: i64| async move {
+            match db.get_pig(offer.chat_id, user_id).await {
+                Ok(Some(pig)) => pig.owner_name,
+                _ => "???".to_string(),
+            }
+        };
+        let from_name = owner_name(offer.from_user).await;
+        let to_name = owner_name(offer.to_user).await;
+
+        let describe = |item: Option<&Loot>| match item {
+            Some(item) => format!("{} {} ({})", item.icon, item.name, self.format_rarity(item.rarity.0)),
+            None => "пропавший предмет".to_string(),
+        };
+        let mark = |accepted: bool| if accepted { "✅" } else { "⏳" };
+
+        let mut lines = if offer.requested_uuid.is_some() {
+            vec![
+                format!("🤝 {} предлагает {} обмен", from_name, to_name),
+                format!("📤 Отдаёт: {}", describe(offered)),
+                format!("📥 Просит: {}", describe(requested)),
+            ]
+        } else {
+            vec![
+                format!("🎁 {} дарит {}", from_name, to_name),
+                format!("📤 {}", describe(offered)),
+            ]
+        };
+
+        lines.push(String::new());
+        lines.push(format!("{} {}", mark(offer.from_accepted), from_name));
+        lines.push(format!("{} {}", mark(offer.to_accepted), to_name));
+        lines.push(format!("Оба должны подтвердить в течение {} мин.", config.game.trade_offer_ttl));
+
+        lines.join("\n")
+    }
+
+    /// Handles `trade:<offer_id>:a` (accept) and `trade:<offer_id>:d` (decline).
+    pub async fn handle_offer_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        message: &MaybeInaccessibleMessage,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let user_id = query.from.id.0 as i64;
+        let Some(offer_id) = data.get::<i32>(0) else {
+            return answer_stale(bot, query).await;
+        };
+        let accept = data.field(1) == Some("a");
+
+        let offer = match db.get_loot_offer(offer_id).await {
+            Ok(Some(offer)) => offer,
+            Ok(None) => {
+                bot.answer_callback_query(&query.id).text("Сделка не найдена").await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if user_id != offer.from_user && user_id != offer.to_user {
+            bot.answer_callback_query(&query.id).text("🖕🤣 Это не твоя сделка!").await?;
+            return Ok(());
+        }
+
+        if offer.status != "pending" {
+            bot.answer_callback_query(&query.id).text("Сделка уже закрыта").await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let (final_text, answer) = if offer.expires_at <= now {
+            db.close_loot_offer(offer.id, "expired").await.ok();
+            (Some("⌛ Время на сделку вышло".to_string()), "Сделка просрочена")
+        } else if !accept {
+            db.close_loot_offer(offer.id, "declined").await.ok();
+            (Some(format!("❌ Сделка отменена игроком {}", query.from.full_name())), "Сделка отменена")
+        } else {
+            match db.accept_loot_offer(offer.id, user_id, now).await {
+                Ok(Some(updated)) if updated.from_accepted && updated.to_accepted => {
+                    self.complete_offer(&updated, now, db, config).await
+                }
+                Ok(Some(updated)) => {
+                    let offered = db.get_loot_by_uuid(updated.offered_uuid).await.ok().flatten();
+                    let requested = match updated.requested_uuid {
+                        Some(uuid) => db.get_loot_by_uuid(uuid).await.ok().flatten(),
+                        None => None,
+                    };
+                    let text = self.format_offer(&updated, offered.as_ref(), requested.as_ref(), db, config).await;
+                    bot.edit_message_text(message.chat().id, message.id(), text)
+                        .reply_markup(self.create_offer_keyboard(updated.id))
+                        .await?;
+                    (None, "Вы подтвердили сделку")
+                }
+                Ok(None) => (None, "Сделка уже закрыта"),
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    (None, "Ошибка базы данных")
+                }
+            }
+        };
+
+        if let Some(text) = final_text {
+            bot.edit_message_text(message.chat().id, message.id(), text).await?;
+        }
+        bot.answer_callback_query(&query.id).text(answer).await?;
+
+        Ok(())
+    }
+
+    /// Executes a fully accepted offer and describes the outcome.
+    async fn complete_offer(
+        &self,
+        offer: &LootOffer,
+        now: f64,
+        db: &Database,
+        config: &Config,
+    ) -> (Option<String>, &'static str) {
+        let recipient_max_items = match db.get_pig(offer.chat_id, offer.to_user).await {
+            Ok(Some(pig)) => self.max_items(&pig, config),
+            _ => config.game.max_items as i64,
+        };
+        let offered = db.get_loot_by_uuid(offer.offered_uuid).await.ok().flatten();
+
+        match db.execute_loot_offer(offer.id, now, recipient_max_items).await {
+            Ok(Ok(())) => {
+                let item = offered
+                    .map(|item| format!("{} {}", item.icon, item.name))
+                    .unwrap_or_default();
+                let text = if offer.requested_uuid.is_some() {
+                    format!("🤝 Обмен состоялся! {} сменил хозяина", item)
+                } else {
+                    format!("🎁 Подарок вручён: {}", item)
+                };
+                (Some(text), "Сделка завершена")
+            }
+            Ok(Err(failure)) => {
+                db.close_loot_offer(offer.id, "failed").await.ok();
+                let reason = match failure {
+                    OfferFailure::Closed => "⌛ Сделка уже закрыта или просрочена",
+                    OfferFailure::ItemMissing => "❌ Сделка сорвалась: предмета больше нет у владельца",
+                    OfferFailure::InventoryFull => "❌ Сделка сорвалась: у получателя полный инвентарь",
+                };
+                (Some(reason.to_string()), "Сделка сорвалась")
+            }
+            Err(e) => {
+                log::error!("Failed to execute loot offer: {}", e);
+                (None, "Ошибка базы данных")
+            }
+        }
+    }
+}
@@ -0,0 +1,141 @@
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::config::Config;
+use crate::database::Database;
+use super::buildings::Building;
+use super::helpers::unix_now;
+
+impl super::PigGameModule {
+    /// `/poison <name>` or `/poison` in reply: pay salo for a chance to poison someone else's pig.
+    pub async fn handle_poison_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+        let cost = config.game.poison_cost;
+
+        let target = match self.resolve_target_pig(&msg, &args.join(" "), db).await {
+            Ok(Some(target)) => target,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "Кого травим? Ответь на сообщение жертвы или укажи имя свиньи: /poison <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        if target.user_id == user_id {
+            bot.send_message(msg.chat.id, "Травить своего хряка? Ты чё, ебанутый?")
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        }
+
+        if target.poisoned {
+            bot.send_message(msg.chat.id, format!("🤢 {} и так уже отравлен", target.name))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        }
+
+        let success = rand::random::<f64>() < config.game.poison_chance;
+        let text = match db.poison_pig(chat_id, user_id, target.user_id, cost, success).await {
+            Ok(None) => format!("Отрава стоит {} 🥓, а у тебя столько нет", cost),
+            Ok(Some(true)) => format!("☠️ {} сожрал отраву и отравился! Теперь ему нужен ветеринар", target.name),
+            Ok(Some(false)) => format!("🐽 {} понюхал отраву и не стал жрать. {} 🥓 потрачено зря", target.name, cost),
+            Err(e) => {
+                log::error!("Failed to poison pig: {}", e);
+                "Ошибка базы данных".to_string()
+            }
+        };
+
+        bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+        Ok(())
+    }
+
+    /// `/vet`: cures the caller's pig and collects the clinic's passive income.
+    pub async fn handle_vet_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+        let user_id = msg.from.as_ref().map(|u| u.id.0 as i64).unwrap_or(0);
+
+        let mut pig = match db.get_pig(chat_id, user_id).await {
+            Ok(Some(pig)) => pig,
+            Ok(None) => {
+                bot.send_message(msg.chat.id, "У вас нет свиньи! Создайте её командой /pig <имя>")
+                    .reply_to(msg.id)
+                    .await?;
+                return Ok(());
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let mut lines = Vec::new();
+
+        if pig.poisoned {
+            // Owners of a clinic treat their own pig for free.
+            let cost = if pig.vetclinic > 0 { 0 } else { config.game.vet_cure_cost };
+            match db.cure_pig(chat_id, user_id, cost).await {
+                Ok(Some(cured)) => {
+                    pig = cured;
+                    lines.push(format!("💉 {} вылечен! Лечение стоило {} 🥓", pig.name, cost));
+                }
+                Ok(None) => {
+                    lines.push(format!("🤢 Лечение стоит {} 🥓, а у вас {}. Постройте ветклинику в /build", cost, pig.salo));
+                }
+                Err(e) => {
+                    log::error!("Failed to cure pig: {}", e);
+                    lines.push("Ошибка базы данных".to_string());
+                }
+            }
+        } else {
+            lines.push(format!("🩺 {} здоров", pig.name));
+        }
+
+        if pig.vetclinic > 0 {
+            let now = unix_now();
+            let daily_income = self.building_effect(Building::Vetclinic, &pig, config);
+            let amount = self.calculate_vet_income(now - pig.vet_last_pickup, daily_income);
+
+            if amount > 0 {
+                match db.collect_vet_income(chat_id, user_id, amount, pig.vet_last_pickup, now).await {
+                    Ok(Some(updated)) => {
+                        lines.push(format!("🏥 Клиника принесла {} 🥓. На складе: {}", amount, updated.salo));
+                    }
+                    Ok(None) => lines.push("🏥 Доход клиники уже собран".to_string()),
+                    Err(e) => {
+                        log::error!("Failed to collect vet income: {}", e);
+                        lines.push("Ошибка базы данных".to_string());
+                    }
+                }
+            } else {
+                lines.push("🏥 Клиника пока ничего не заработала".to_string());
+            }
+        }
+
+        bot.send_message(msg.chat.id, lines.join("\n")).reply_to(msg.id).await?;
+        Ok(())
+    }
+}
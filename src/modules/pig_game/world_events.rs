@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use std::time::Duration;
+use rand::prelude::*;
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::Message,
+};
+use crate::config::{Config, WorldEventConfig, WorldEventsConfig};
+use crate::database::Database;
+use crate::modules::permissions::{Role, deny, has_role};
+use super::helpers::{format_duration, unix_now, GrowModifiers};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorldEvent {
+    SwineFlu,
+    Harvest,
+    WolfRaid,
+}
+
+impl WorldEvent {
+    pub const ALL: [WorldEvent; 3] = [WorldEvent::SwineFlu, WorldEvent::Harvest, WorldEvent::WolfRaid];
+
+    /// Config key and `world_events.kind` value.
+    pub fn key(self) -> &'static str {
+        match self {
+            WorldEvent::SwineFlu => "swine_flu",
+            WorldEvent::Harvest => "harvest",
+            WorldEvent::WolfRaid => "wolf_raid",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|e| e.key() == key)
+    }
+
+    pub fn config(self, events: &WorldEventsConfig) -> &WorldEventConfig {
+        match self {
+            WorldEvent::SwineFlu => &events.swine_flu,
+            WorldEvent::Harvest => &events.harvest,
+            WorldEvent::WolfRaid => &events.wolf_raid,
+        }
+    }
+}
+
+/// Events that start this roll, each rolled independently.
+pub fn roll_world_events(events: &WorldEventsConfig, rng: &mut impl Rng) -> Vec<WorldEvent> {
+    WorldEvent::ALL
+        .into_iter()
+        .filter(|event| rng.random_bool(event.config(events).chance.clamp(0.0, 1.0)))
+        .collect()
+}
+
+/// Folds running events into a feed's modifiers and returns notes for the grow message.
+pub fn apply_world_events(modifiers: &mut GrowModifiers, active: &[WorldEvent], events: &WorldEventsConfig) -> Vec<String> {
+    let mut notes = Vec::new();
+    for &event in active {
+        let event_config = event.config(events);
+        if event == WorldEvent::Harvest {
+            modifiers.gain_multiplier *= event_config.effect;
+            notes.push(format!("{} {}: прирост x{}", event_config.icon, event_config.name, event_config.effect));
+        }
+    }
+    notes
+}
+
+/// Spawns the job that rolls world events in every chat that allows them.
+/// An `INTERVAL` of zero disables world events.
+pub fn spawn_world_event_job(bot: Bot, db: Arc<Database>, config: Arc<Config>) {
+    if config.events.interval == 0 {
+        log::info!("World events are disabled");
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.events.interval * 60));
+        // The first tick fires immediately; skip it so restarts do not roll extra events.
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            if let Err(e) = roll_chat_events(&bot, &db, &config.events).await {
+                log::error!("World event job failed: {}", e);
+            }
+        }
+    });
+}
+
+async fn roll_chat_events(bot: &Bot, db: &Database, events: &WorldEventsConfig) -> Result<(), sqlx::Error> {
+    for chat_id in db.get_event_chats(super::MODULE_KEY).await? {
+        let rolled = roll_world_events(events, &mut rand::rng());
+        for event in rolled {
+            let event_config = event.config(events);
+            let now = unix_now();
+            if !db.start_world_event(chat_id, event.key(), now, now + (event_config.duration * 60) as f64).await? {
+                continue;
+            }
+
+            log::info!("World event {} started in chat {}", event.key(), chat_id);
+            let text = start_world_event(event, chat_id, db, event_config).await?;
+            if let Err(e) = bot.send_message(ChatId(chat_id), text).await {
+                log::warn!("Failed to announce world event in chat {}: {}", chat_id, e);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Applies the one-off part of an event and returns its announcement.
+async fn start_world_event(event: WorldEvent, chat_id: i64, db: &Database, event_config: &WorldEventConfig) -> Result<String, sqlx::Error> {
+    let header = format!("{} {}!", event_config.icon, event_config.name);
+    let text = match event {
+        WorldEvent::SwineFlu => {
+            db.apply_weight_loss(chat_id, event_config.effect).await?;
+            format!("{}\nВсе свиньи чата слегли и похудели на {:.0}% 🤮", header, event_config.effect * 100.0)
+        }
+        WorldEvent::Harvest => format!(
+            "{}\nСледующие {} прирост при кормёжке x{}. Жрите, пока дают!",
+            header,
+            format_duration(event_config.duration * 60),
+            event_config.effect
+        ),
+        WorldEvent::WolfRaid => {
+            let victims = db.steal_unprotected_salo(chat_id, event_config.effect).await?;
+            if victims.is_empty() {
+                format!("{}\nНо у всех стоят сараи, волки ушли ни с чем", header)
+            } else {
+                let mut lines = vec![format!("{}\nВолки обнесли свиней без сарая:", header)];
+                lines.extend(victims.iter().map(|(name, stolen)| format!("🐖 {}: -{} 🥓", name, stolen)));
+                lines.push("Постройте сарай в /build, чтобы спать спокойно".to_string());
+                lines.join("\n")
+            }
+        }
+    };
+    Ok(text)
+}
+
+impl super::PigGameModule {
+    /// `/events` lists running and possible world events; `/events off|on` toggles them (admins and game masters only).
+    pub async fn handle_events_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        args: Vec<&str>,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let chat_id = msg.chat.id.0;
+
+        if let Some(&toggle) = args.first() {
+            let disabled = match toggle {
+                "off" | "выкл" => true,
+                "on" | "вкл" => false,
+                _ => {
+                    bot.send_message(msg.chat.id, "Использование: /events [on|off]").reply_to(msg.id).await?;
+                    return Ok(());
+                }
+            };
+
+            let Some(user) = msg.from.as_ref() else {
+                return Ok(());
+            };
+            let role = Role::Custom(super::GAME_MASTER);
+            if !has_role(&bot, db, config, msg.chat.id, user.id, role).await {
+                return deny(&bot, &msg, role).await;
+            }
+
+            let text = match db.set_events_disabled(chat_id, disabled).await {
+                Ok(()) if disabled => "🔕 События мира в этом чате отключены",
+                Ok(()) => "🌍 События мира снова случаются в этом чате",
+                Err(e) => {
+                    log::error!("Database error: {}", e);
+                    "Ошибка базы данных"
+                }
+            };
+            bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        let now = unix_now();
+        let status = async {
+            let settings = db.get_chat_settings(chat_id).await?;
+            let active = db.get_active_world_events(chat_id, now).await?;
+            Ok::<_, sqlx::Error>((settings, active))
+        };
+
+        let (settings, active) = match status.await {
+            Ok(status) => status,
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.send_message(msg.chat.id, "Ошибка базы данных").await?;
+                return Ok(());
+            }
+        };
+
+        let mut lines = vec!["🌍 События мира".to_string()];
+        if config.events.interval == 0 {
+            lines.push("Отключены на этом сервере".to_string());
+        } else if settings.events_disabled {
+            lines.push("Отключены в этом чате. Включить: /events on".to_string());
+        } else {
+            let running: Vec<String> = active
+                .iter()
+                .filter_map(|record| {
+                    let event_config = WorldEvent::from_key(&record.kind)?.config(&config.events);
+                    let remaining = (record.ends_at - now).max(0.0) as u64;
+                    Some(format!("{} {} — ещё {}", event_config.icon, event_config.name, format_duration(remaining)))
+                })
+                .collect();
+            if running.is_empty() {
+                lines.push("Сейчас всё спокойно".to_string());
+            } else {
+                lines.push("Сейчас идёт:".to_string());
+                lines.extend(running);
+            }
+        }
+
+        lines.push(format!("\nВозможные события (проверка раз в {}):", format_duration(config.events.interval * 60)));
+        for event in WorldEvent::ALL {
+            let event_config = event.config(&config.events);
+            lines.push(format!(
+                "{} {}: {} ({:.0}%)",
+                event_config.icon,
+                event_config.name,
+                event_config.description,
+                event_config.chance * 100.0
+            ));
+        }
+
+        bot.send_message(msg.chat.id, lines.join("\n")).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn event_chances_are_respected() {
+        let mut events = WorldEventsConfig::default();
+        events.swine_flu.chance = 1.0;
+        events.harvest.chance = 0.0;
+        events.wolf_raid.chance = 1.0;
+
+        let mut rng = StdRng::seed_from_u64(3);
+        assert_eq!(roll_world_events(&events, &mut rng), vec![WorldEvent::SwineFlu, WorldEvent::WolfRaid]);
+    }
+
+    #[test]
+    fn harvest_multiplies_gain_only() {
+        let events = WorldEventsConfig::default();
+        let mut modifiers = GrowModifiers::default();
+        let notes = apply_world_events(&mut modifiers, &[WorldEvent::Harvest, WorldEvent::WolfRaid], &events);
+        assert_eq!(modifiers.gain_multiplier, events.harvest.effect);
+        assert_eq!(modifiers.loss_multiplier, 1.0);
+        assert_eq!(notes.len(), 1);
+    }
+
+    #[test]
+    fn event_keys_round_trip() {
+        for event in WorldEvent::ALL {
+            assert_eq!(WorldEvent::from_key(event.key()), Some(event));
+        }
+    }
+}
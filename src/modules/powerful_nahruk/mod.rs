@@ -1,6 +1,7 @@
 use crate::config::Config;
 use crate::database::Database;
 use crate::modules::BotModule;
+use crate::modules::command::{CommandSpec, ParsedCommand};
 
 use async_trait::async_trait;
 use teloxide::{prelude::*, sugar::request::RequestReplyExt, types::Message};
@@ -14,7 +15,7 @@ impl PowerfulNahrukModule {
   }
 
   async fn check_nahruk(&self, msg: &str) -> String {
-    let trigger_words = vec!["украина", "хохол", "хохл"];
+    let trigger_words = ["украина", "хохол", "хохл"];
     if trigger_words.iter().any(|&word| msg.contains(word)) {
       "Ваш нахрюк заблокирован ❌".to_string()
     } else {
@@ -29,7 +30,16 @@ impl BotModule for PowerfulNahrukModule {
     "Powerfull Nahruk"
   }
 
-  fn commands(&self) -> Vec<(&'static str, &'static str)> {
+  fn key(&self) -> &'static str {
+    "nahruk"
+  }
+
+  /// A blocked nahruk is answered before anything else sees the message.
+  fn priority(&self) -> i32 {
+    10
+  }
+
+  fn commands(&self) -> Vec<CommandSpec> {
     vec![]
   }
 
@@ -37,8 +47,7 @@ impl BotModule for PowerfulNahrukModule {
     &self,
     _bot: Bot,
     _msg: Message,
-    _command: &str,
-    _args: Vec<&str>,
+    _command: &ParsedCommand,
     _db: &Database,
     _config: &Config,
   ) -> ResponseResult<()> {
@@ -46,7 +55,7 @@ impl BotModule for PowerfulNahrukModule {
   }
 
   async fn handle_message(&self, bot: Bot, msg: Message, _db: &Database, _config: &Config) -> ResponseResult<bool> {
-    let nahruk = self.check_nahruk(&msg.text().unwrap_or("")).await;
+    let nahruk = self.check_nahruk(msg.text().unwrap_or("")).await;
     if !nahruk.is_empty() {
       bot
         .send_message(msg.chat.id, nahruk)
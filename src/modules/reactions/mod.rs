@@ -1,6 +1,7 @@
 use crate::config::Config;
 use crate::database::Database;
-use crate::modules::BotModule;
+use crate::modules::{BotModule, Dispatch};
+use crate::modules::command::{CommandSpec, ParsedCommand};
 use async_trait::async_trait;
 use rand::prelude::*;
 use teloxide::types::ReactionType;
@@ -15,7 +16,7 @@ impl ReactionsModule {
 
   fn react_to_message(&self) -> &'static str {
     let mut rng = rand::rng();
-    let reactions = vec!["🤡", "💩", "🤣", "💊", "😁", "😨"];
+    let reactions = ["🤡", "💩", "🤣", "💊", "😁", "😨"];
     if rand::random::<u8>() < 25 {
       reactions[rng.random_range(0..reactions.len())]
     } else {
@@ -30,7 +31,20 @@ impl BotModule for ReactionsModule {
     "Reactions Module"
   }
 
-  fn commands(&self) -> Vec<(&'static str, &'static str)> {
+  fn key(&self) -> &'static str {
+    "reactions"
+  }
+
+  /// Reacts last and never keeps a message from the other modules.
+  fn priority(&self) -> i32 {
+    -10
+  }
+
+  fn dispatch(&self) -> Dispatch {
+    Dispatch::FanOut
+  }
+
+  fn commands(&self) -> Vec<CommandSpec> {
     vec![]
   }
 
@@ -38,8 +52,7 @@ impl BotModule for ReactionsModule {
     &self,
     _bot: Bot,
     _msg: Message,
-    _command: &str,
-    _args: Vec<&str>,
+    _command: &ParsedCommand,
     _db: &Database,
     _config: &Config,
   ) -> ResponseResult<()> {
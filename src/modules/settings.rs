@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex};
+use teloxide::{
+    prelude::*,
+    sugar::request::RequestReplyExt,
+    types::{CallbackQuery, ChatId, InlineKeyboardMarkup, Message},
+};
+use crate::config::Config;
+use crate::database::Database;
+use super::ModuleManager;
+use super::callback::{CallbackData, answer_stale};
+use super::command::{ArgKind, CommandScope, CommandSpec, ParsedCommand};
+use super::permissions::{Role, deny_callback, has_role};
+
+/// Callback namespace of the /modules keyboard.
+pub const MODULES_NAMESPACE: &str = "modules";
+
+/// Commands the manager answers itself rather than a module.
+pub fn settings_commands() -> Vec<CommandSpec> {
+    vec![
+        CommandSpec::new("modules", "Модули бота в этом чате")
+            .translate("en", "Bot modules in this chat")
+            .scope(CommandScope::Admins)
+            .role(Role::ChatAdmin),
+        CommandSpec::new("enable", "Включить модуль в этом чате")
+            .translate("en", "Enable a module in this chat")
+            .scope(CommandScope::Admins)
+            .role(Role::ChatAdmin)
+            .arg("модуль", ArgKind::Word),
+        CommandSpec::new("disable", "Выключить модуль в этом чате")
+            .translate("en", "Disable a module in this chat")
+            .scope(CommandScope::Admins)
+            .role(Role::ChatAdmin)
+            .arg("модуль", ArgKind::Word),
+        CommandSpec::new("synccommands", "Заново опубликовать меню команд")
+            .translate("en", "Republish the command menus")
+            .scope(CommandScope::Private)
+            .role(Role::Superuser),
+    ]
+}
+
+/// Modules switched off per chat. Only /enable, /disable and the /modules
+/// keyboard change them, and they store the new list here.
+static DISABLED_MODULES: LazyLock<Mutex<HashMap<ChatId, Vec<String>>>> = LazyLock::new(Default::default);
+
+fn remember_disabled(chat_id: ChatId, disabled: &[String]) {
+    DISABLED_MODULES.lock().unwrap().insert(chat_id, disabled.to_vec());
+}
+
+impl ModuleManager {
+    /// Keys of the modules switched off in a chat, read from the database once
+    /// per chat. Lookup failures leave every module on and are not cached.
+    pub(super) async fn disabled_modules(&self, chat_id: ChatId, db: &Database) -> Vec<String> {
+        if let Some(disabled) = DISABLED_MODULES.lock().unwrap().get(&chat_id) {
+            return disabled.clone();
+        }
+
+        match db.get_chat_settings(chat_id.0).await {
+            Ok(settings) => {
+                remember_disabled(chat_id, &settings.disabled_modules);
+                settings.disabled_modules
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                Vec::new()
+            }
+        }
+    }
+
+    fn module_key(&self, name: &str) -> Option<&'static str> {
+        self.modules
+            .iter()
+            .map(|module| module.key())
+            .find(|key| key.eq_ignore_ascii_case(name))
+    }
+
+    fn format_modules(&self, disabled: &[String]) -> String {
+        let mut lines = vec!["🧩 Модули в этом чате:".to_string()];
+        for module in &self.modules {
+            let on = !disabled.iter().any(|key| key == module.key());
+            lines.push(format!("{} {} ({})", if on { "✅" } else { "❌" }, module.name(), module.key()));
+        }
+        lines.push("\nНажми на модуль, чтобы переключить его".to_string());
+        lines.join("\n")
+    }
+
+    fn create_modules_keyboard(&self, disabled: &[String]) -> InlineKeyboardMarkup {
+        let rows = self
+            .modules
+            .iter()
+            .map(|module| {
+                let on = !disabled.iter().any(|key| key == module.key());
+                let text = format!("{} {}", if on { "✅" } else { "❌" }, module.name());
+                vec![CallbackData::new(MODULES_NAMESPACE).with(module.key()).button(text)]
+            })
+            .collect::<Vec<_>>();
+        InlineKeyboardMarkup::new(rows)
+    }
+
+    /// `/modules`, `/enable <module>`, `/disable <module>` and `/synccommands`.
+    /// Their specs restrict who gets here.
+    pub(super) async fn handle_settings_command(
+        &self,
+        bot: Bot,
+        msg: Message,
+        command: &ParsedCommand,
+        db: &Database,
+    ) -> ResponseResult<()> {
+        if command.name == "synccommands" {
+            let text = match self.publish_commands(&bot).await {
+                Ok(()) => "✅ Меню команд обновлено",
+                Err(e) => {
+                    log::error!("Failed to publish bot commands: {}", e);
+                    "Не удалось обновить меню команд"
+                }
+            };
+            bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+            return Ok(());
+        }
+
+        if command.name == "modules" {
+            let disabled = self.disabled_modules(msg.chat.id, db).await;
+            bot.send_message(msg.chat.id, self.format_modules(&disabled))
+                .reply_markup(self.create_modules_keyboard(&disabled))
+                .await?;
+            return Ok(());
+        }
+
+        let name = command.text(0).unwrap_or_default();
+        let Some(key) = self.module_key(name) else {
+            let keys: Vec<&str> = self.modules.iter().map(|module| module.key()).collect();
+            bot.send_message(msg.chat.id, format!("Нет модуля «{}». Есть: {}", name, keys.join(", ")))
+                .reply_to(msg.id)
+                .await?;
+            return Ok(());
+        };
+
+        let disable = command.name == "disable";
+        let text = match db.set_module_disabled(msg.chat.id.0, key, disable).await {
+            Ok(disabled) => {
+                remember_disabled(msg.chat.id, &disabled);
+                if disable {
+                    format!("❌ Модуль {} выключен в этом чате", key)
+                } else {
+                    format!("✅ Модуль {} включён в этом чате", key)
+                }
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                "Ошибка базы данных".to_string()
+            }
+        };
+        bot.send_message(msg.chat.id, text).reply_to(msg.id).await?;
+        Ok(())
+    }
+
+    /// Handles `modules:<key>` presses, which toggle the module.
+    pub(super) async fn handle_settings_callback(
+        &self,
+        bot: &Bot,
+        query: &CallbackQuery,
+        data: &CallbackData,
+        db: &Database,
+        config: &Config,
+    ) -> ResponseResult<()> {
+        let (Some(message), Some(key)) = (&query.message, data.field(0).and_then(|key| self.module_key(key))) else {
+            return answer_stale(bot, query).await;
+        };
+
+        if !has_role(bot, db, config, message.chat().id, query.from.id, Role::ChatAdmin).await {
+            return deny_callback(bot, query, Role::ChatAdmin).await;
+        }
+
+        let disable = !self.disabled_modules(message.chat().id, db).await.iter().any(|k| k == key);
+        match db.set_module_disabled(message.chat().id.0, key, disable).await {
+            Ok(disabled) => {
+                remember_disabled(message.chat().id, &disabled);
+                bot.answer_callback_query(&query.id)
+                    .text(if disable { format!("❌ {} выключен", key) } else { format!("✅ {} включён", key) })
+                    .await?;
+                bot.edit_message_text(message.chat().id, message.id(), self.format_modules(&disabled))
+                    .reply_markup(self.create_modules_keyboard(&disabled))
+                    .await?;
+            }
+            Err(e) => {
+                log::error!("Database error: {}", e);
+                bot.answer_callback_query(&query.id).text("Ошибка базы данных").await?;
+            }
+        }
+
+        Ok(())
+    }
+}